@@ -0,0 +1,66 @@
+//! Micro-benchmark of [`Cursor::bump_two`] against its previous implementation, which computed
+//! the byte position with [`char::len_utf8`] for each character instead of from the remaining
+//! input length.
+//!
+//! Run with `cargo bench --bench bump_two`.
+
+use std::hint::black_box;
+use std::str::Chars;
+use std::time::{Duration, Instant};
+
+use simple_cursor::Cursor;
+
+/// Number of runs of each case, of which the fastest is reported.
+const RUNS: usize = 20;
+
+/// Minimal cursor with the previous implementation of `bump_two`.
+struct LenUtf8Cursor<'a> {
+    chars: Chars<'a>,
+    byte_pos: usize,
+}
+
+impl LenUtf8Cursor<'_> {
+    fn bump_two(&mut self) -> (Option<char>, Option<char>) {
+        let (c1, c2) = (self.chars.next(), self.chars.next());
+        self.byte_pos += c1.map(char::len_utf8).unwrap_or_default();
+        self.byte_pos += c2.map(char::len_utf8).unwrap_or_default();
+        (c1, c2)
+    }
+}
+
+/// The fastest of [`RUNS`] runs of `f`.
+fn fastest(mut f: impl FnMut() -> usize) -> Duration {
+    (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            black_box(f());
+            start.elapsed()
+        })
+        .min()
+        .unwrap_or_default()
+}
+
+fn main() {
+    let cases = [
+        ("ascii", "ab".repeat(1 << 20)),
+        ("multi-byte", "é竜".repeat(1 << 20)),
+        ("mixed", "a竜\n".repeat(1 << 20)),
+    ];
+    for (name, input) in &cases {
+        let input = black_box(input.as_str());
+        let len_utf8 = fastest(|| {
+            let mut cursor = LenUtf8Cursor {
+                chars: input.chars(),
+                byte_pos: 0,
+            };
+            while cursor.bump_two().1.is_some() {}
+            cursor.byte_pos
+        });
+        let batched = fastest(|| {
+            let mut cursor = Cursor::new(input);
+            while cursor.bump_two().1.is_some() {}
+            cursor.byte_pos()
+        });
+        println!("{name:<10}  len_utf8: {len_utf8:>10.2?}  batched: {batched:>10.2?}");
+    }
+}
@@ -35,26 +35,216 @@
 //! assert_eq!("foobar", &input[ident_start..ident_end]);
 //! assert_eq!("竜<!>", &input[rest_start..rest_end]);
 //! ```
+//!
+//! # Features
+//! No features are enabled by default, so the crate doesn't require an allocator.
+//! - `alloc`: enables `LineIndex` and `Cursor::build_line_index`, which allocate.
+//! - `std`: implements `std::error::Error` for the error types. Implies `alloc`.
 
 #![no_std]
 
-use core::str::Chars;
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+use core::cmp::Ordering;
+use core::fmt;
+use core::ops::{ControlFlow, Range, RangeInclusive};
+use core::str::{Chars, Utf8Error};
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use tracking::{LineTracking, Track, Tracking};
 
 /// Abstraction over a character iterator.
-pub struct Cursor<'a> {
+///
+/// By default, the cursor only keeps track of its byte and character positions. Keeping track of lines and
+/// columns has a cost on every consumed character, so it is opt-in: see [`Cursor::tracked`] and
+/// [`TrackedCursor`]. A cursor which doesn't track them doesn't store them either.
+#[derive(Clone)]
+pub struct Cursor<'a, const TRACK: bool = false>
+where
+    Track<TRACK>: Tracking,
+{
+    /// Original input string.
+    input: &'a str,
     /// Raw charactor iterator.
     chars: Chars<'a>,
     /// Current byte position of the cursor.
     byte_pos: usize,
+    /// Number of characters consumed by the cursor.
+    char_pos: usize,
+    /// Line and column tracking state, only stored if `TRACK` is set.
+    tracking: <Track<TRACK> as Tracking>::State,
 }
 
+/// A [`Cursor`] which keeps track of its line and column.
+pub type TrackedCursor<'a> = Cursor<'a, true>;
+
 impl<'a> Cursor<'a> {
     /// Creates a new [`Cursor`] from an input string.
+    ///
+    /// This isn't a `const fn` because creating the [`Chars`] iterator with [`str::chars`] isn't
+    /// possible in const contexts yet. Creating a cursor is cheap though: it doesn't scan the
+    /// input.
     pub fn new(input: &'a str) -> Self {
+        Self::with_offset(input, 0)
+    }
+
+    /// Creates a new [`Cursor`] from input bytes, which must be valid UTF-8.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, Utf8Error> {
+        core::str::from_utf8(bytes).map(Self::new)
+    }
+
+    /// Creates a new [`Cursor`] over the first `end_byte` bytes of an input string.
+    ///
+    /// The cursor treats `end_byte` as the end of the input, even if more input follows. Returns
+    /// a [`NotCharBoundary`] error if `end_byte` is out of bounds or not on a char boundary.
+    pub fn limited(input: &'a str, end_byte: usize) -> Result<Self, NotCharBoundary> {
+        input
+            .get(..end_byte)
+            .map(Self::new)
+            .ok_or(NotCharBoundary { byte_pos: end_byte })
+    }
+
+    /// Creates a new [`Cursor`] from an input string which starts at byte position `start_byte`.
+    ///
+    /// This is useful to lex a fragment of a larger document: `input` should be the fragment and
+    /// `start_byte` its position in the document. All positions reported by the cursor are then
+    /// relative to the whole document, not to `input`.
+    pub fn with_offset(input: &'a str, start_byte: usize) -> Self {
+        Self::from_start(
+            input,
+            Position {
+                byte: start_byte,
+                line: 1,
+                column: 0,
+            },
+        )
+    }
+
+    /// Enables keeping track of the line and column of the cursor.
+    ///
+    /// If the cursor has already consumed some input, it is rescanned to compute them.
+    pub fn tracked(self) -> TrackedCursor<'a> {
+        let mut cursor = Cursor::from_start(self.input, self.start());
+        cursor.advance(self.consumed().len());
+        cursor
+    }
+}
+
+impl<'a> TrackedCursor<'a> {
+    /// Creates a new line and column tracking [`Cursor`] from an input string which starts at the
+    /// given [`Position`].
+    ///
+    /// Like [`Cursor::with_offset`], but the line and column of the start of `input` in the
+    /// document are also known.
+    pub fn new_at(input: &'a str, start: Position) -> Self {
+        Self::from_start(input, start)
+    }
+
+    /// The current line of the cursor into the input string, starting at 1.
+    ///
+    /// `\n`, `\r\n` and a lone `\r` are all treated as a single line break.
+    pub fn line(&self) -> u32 {
+        self.tracking.line
+    }
+
+    /// The current column of the cursor into the current line, starting at 0.
+    ///
+    /// Columns are counted in characters (Unicode scalar values), not bytes. Tabs advance the
+    /// column to the next multiple of the tab width, see [`Cursor::set_tab_width`].
+    pub fn column(&self) -> u32 {
+        self.tracking.column
+    }
+
+    /// The current [`Position`] of the cursor into the input string.
+    pub fn position(&self) -> Position {
+        Position {
+            byte: self.byte_pos,
+            line: self.tracking.line,
+            column: self.tracking.column,
+        }
+    }
+
+    /// Computes the [`Position`] of `byte_pos` in the input string, without moving the cursor.
+    ///
+    /// This is useful to map offsets obtained elsewhere (e.g. from a regex match) to a line and
+    /// column. This is O(n), since the input has to be scanned up to `byte_pos`.
+    ///
+    /// # Panics
+    /// Panics if `byte_pos` is out of bounds or does not fall on a UTF-8 char boundary of the
+    /// input string.
+    pub fn position_at(&self, byte_pos: usize) -> Position {
+        let mut cursor = self.clone();
+        cursor.seek_to(byte_pos);
+        cursor.position()
+    }
+
+    /// Moves the cursor to a [`Position`] previously returned by [`Cursor::position`].
+    ///
+    /// Unlike [`Cursor::seek_to`], the line and column are taken from `position` instead of being
+    /// recomputed, so this doesn't rescan the input from the start. The position must have been
+    /// created from this cursor.
+    ///
+    /// # Panics
+    /// Panics if `position.byte` is out of bounds or does not fall on a UTF-8 char boundary of the
+    /// input string.
+    pub fn rewind_to(&mut self, position: Position) {
+        let Some(index) = self.input_index(position.byte) else {
+            panic!(
+                "{}",
+                NotCharBoundary {
+                    byte_pos: position.byte
+                }
+            );
+        };
+        self.jump_to(index);
+        self.tracking.line = position.line;
+        self.tracking.column = position.column;
+    }
+
+    /// Sets the tab width used to compute columns, which is 1 by default.
+    ///
+    /// The width must be set before consuming any input: moving backwards recomputes columns with
+    /// the current width, so tabs consumed with a different width would be reinterpreted.
+    ///
+    /// # Panics
+    /// Panics if `width` is 0 or if the cursor has already consumed some input.
+    pub fn set_tab_width(&mut self, width: u32) {
+        assert!(width > 0, "tab width must be greater than 0");
+        assert!(
+            self.consumed().is_empty(),
+            "tab width must be set before consuming input"
+        );
+        self.tracking.tab_width = width;
+    }
+}
+
+impl<'a, const TRACK: bool> Cursor<'a, TRACK>
+where
+    Track<TRACK>: Tracking,
+{
+    /// Creates a new [`Cursor`] from an input string which starts at the given [`Position`].
+    fn from_start(input: &'a str, start: Position) -> Self {
         Self {
+            input,
             chars: input.chars(),
-            byte_pos: 0,
+            byte_pos: start.byte,
+            char_pos: 0,
+            tracking: Track::<TRACK>::new(start),
+        }
+    }
+
+    /// Creates a new [`Cursor`] like [`Cursor::from_start`], with the same tab width as this one.
+    fn derive(&self, input: &'a str, start: Position) -> Self {
+        let mut cursor = Self::from_start(input, start);
+        if let Some(tracking) = cursor.tracking_mut() {
+            tracking.tab_width = self.tab_width();
         }
+        cursor
     }
 
     /// Immutable reference to the internal character iterator.
@@ -62,11 +252,163 @@ impl<'a> Cursor<'a> {
         &self.chars
     }
 
+    /// The remaining input as bytes, without advancing the cursor.
+    ///
+    /// This is useful for byte-level scanning. The slice always starts at the current byte
+    /// position, so it stays in sync as characters are consumed.
+    pub fn bytes(&self) -> &'a [u8] {
+        self.chars.as_str().as_bytes()
+    }
+
     /// The current byte position of the cursor into the input string.
     pub fn byte_pos(&self) -> usize {
         self.byte_pos
     }
 
+    /// The current character position of the cursor into the input string.
+    ///
+    /// This is the number of characters (Unicode scalar values) consumed so far.
+    pub fn char_pos(&self) -> usize {
+        self.char_pos
+    }
+
+    /// The total number of lines in the input string, regardless of the cursor position.
+    ///
+    /// This is the number of line breaks plus one, counted like for [`Cursor::line`]. Lines are
+    /// counted in the input string itself, regardless of the start line given to
+    /// [`TrackedCursor::new_at`]: at the end of the input, `line()` equals the start line plus
+    /// `total_lines() - 1`. This is O(n), since the whole input has to be scanned.
+    pub fn total_lines(&self) -> u32 {
+        let mut lines = 1;
+        let mut after_cr = false;
+        for b in self.input.bytes() {
+            // `\r\n` is a single line break
+            if b == b'\r' || (b == b'\n' && !after_cr) {
+                lines += 1;
+            }
+            after_cr = b == b'\r';
+        }
+        lines
+    }
+
+    /// Builds a [`LineIndex`] of the input string, for fast repeated [`Position`] lookups.
+    ///
+    /// This is O(n), since the whole input has to be scanned.
+    #[cfg(feature = "alloc")]
+    pub fn build_line_index(&self) -> LineIndex<'a> {
+        let bytes = self.input.as_bytes();
+        let line_starts = bytes
+            .iter()
+            .enumerate()
+            // `\r\n` is a single line break, so the line starts right after the `\r`
+            .filter(|&(i, &b)| b == b'\r' || (b == b'\n' && (i == 0 || bytes[i - 1] != b'\r')))
+            .map(|(i, _)| i + 1)
+            .collect();
+        LineIndex {
+            input: self.input,
+            start: self.start(),
+            tab_width: self.tab_width(),
+            line_starts,
+        }
+    }
+
+    /// The remaining (unconsumed) input after the cursor.
+    ///
+    /// Its length is always equal to `input.len() - byte_pos()` (plus the starting byte position
+    /// given to [`Cursor::with_offset`], if any).
+    pub fn remaining(&self) -> &'a str {
+        self.chars.as_str()
+    }
+
+    /// The length of the remaining input in bytes.
+    ///
+    /// This is O(1).
+    pub fn remaining_len(&self) -> usize {
+        self.chars.as_str().len()
+    }
+
+    /// The number of characters in the remaining input.
+    ///
+    /// This is O(n), since the remaining input has to be decoded.
+    pub fn remaining_char_count(&self) -> usize {
+        self.chars.clone().count()
+    }
+
+    /// Iterates over the lines of the remaining input, without advancing the cursor.
+    ///
+    /// Each line is yielded along with its [`Span`], excluding the line break. Like for
+    /// [`Cursor::line`], `\n`, `\r\n` and a lone `\r` are all line breaks. Like [`str::lines`],
+    /// a final line break doesn't produce an empty trailing line.
+    pub fn remaining_lines(&self) -> impl Iterator<Item = (Span, &'a str)> {
+        let mut cursor = self.clone();
+        core::iter::from_fn(move || {
+            if cursor.is_eof() {
+                return None;
+            }
+            let start = cursor.byte_pos;
+            let line = cursor.bump_while(|c| c != '\n' && c != '\r');
+            let span = cursor.span_from(start);
+            // Consume the line break
+            cursor.skip_line();
+            Some((span, line))
+        })
+    }
+
+    /// Iterates over the characters of the remaining input along with their byte positions,
+    /// without advancing the cursor.
+    ///
+    /// Like [`str::char_indices`], but the positions are absolute, as returned by
+    /// [`Cursor::byte_pos`].
+    pub fn char_indices(&self) -> impl Iterator<Item = (usize, char)> + 'a {
+        let byte_pos = self.byte_pos;
+        self.chars
+            .as_str()
+            .char_indices()
+            .map(move |(index, c)| (byte_pos + index, c))
+    }
+
+    /// Splits the input at the cursor, returning the `(consumed, remaining)` halves.
+    ///
+    /// ```rust
+    /// use simple_cursor::Cursor;
+    ///
+    /// let mut cursor = Cursor::new("竜王");
+    /// cursor.bump();
+    /// assert_eq!(cursor.split(), ("竜", "王"));
+    /// ```
+    pub fn split(&self) -> (&'a str, &'a str) {
+        (self.consumed(), self.chars.as_str())
+    }
+
+    /// The already consumed input before the cursor.
+    pub fn consumed(&self) -> &'a str {
+        &self.input[..self.input.len() - self.chars.as_str().len()]
+    }
+
+    /// Whether the cursor has reached the end of the input string.
+    pub fn is_eof(&self) -> bool {
+        self.chars.as_str().is_empty()
+    }
+
+    /// Whether the remaining input is empty or only contains whitespace, as defined by
+    /// [`char::is_whitespace`].
+    pub fn is_eof_ignoring_whitespace(&self) -> bool {
+        self.chars.as_str().trim_start().is_empty()
+    }
+
+    /// Checks that the cursor has reached the end of the input string, or returns a
+    /// [`TrailingInput`] error with the remaining input.
+    pub fn expect_eof(&self) -> Result<(), TrailingInput<'a>> {
+        if self.is_eof() {
+            Ok(())
+        } else {
+            Err(TrailingInput {
+                remaining: self.chars.as_str(),
+                byte_pos: self.byte_pos,
+            })
+        }
+    }
+
     /// Peeks the next character without advancing the cursor.
     pub fn peek(&self) -> Option<char> {
         // Cloning a [`Chars`] iterator is cheap.
@@ -80,25 +422,587 @@ impl<'a> Cursor<'a> {
         (cloned.next(), cloned.next())
     }
 
+    /// Peeks the next `N` characters without advancing the cursor.
+    ///
+    /// Positions past the end of the input are filled with `None`.
+    pub fn peek_array<const N: usize>(&self) -> [Option<char>; N] {
+        // Cloning a [`Chars`] iterator is cheap.
+        let mut cloned = self.chars.clone();
+        core::array::from_fn(|_| cloned.next())
+    }
+
+    /// Peeks the `n`th next character (zero-indexed) without advancing the cursor.
+    ///
+    /// `peek_nth(0)` is equivalent to [`Cursor::peek`]. Returns `None` if fewer than `n + 1`
+    /// characters remain.
+    pub fn peek_nth(&self, n: usize) -> Option<char> {
+        // Cloning a [`Chars`] iterator is cheap.
+        self.chars.clone().nth(n)
+    }
+
+    /// Returns the index of the first of `prefixes` the remaining input starts with, without
+    /// advancing the cursor.
+    ///
+    /// Earlier entries win when several prefixes match.
+    pub fn starts_with_any(&self, prefixes: &[&str]) -> Option<usize> {
+        prefixes.iter().position(|prefix| self.starts_with(prefix))
+    }
+
+    /// Whether the next character exists and satisfies `predicate`, without advancing the cursor.
+    pub fn peek_is<F: FnOnce(char) -> bool>(&self, predicate: F) -> bool {
+        self.peek().is_some_and(predicate)
+    }
+
+    /// Whether the next character exists and falls within `range`, without advancing the cursor.
+    pub fn peek_in_range(&self, range: RangeInclusive<char>) -> bool {
+        self.peek_is(|c| range.contains(&c))
+    }
+
+    /// The byte position at which the `n`th next character (zero-indexed) starts, without
+    /// advancing the cursor.
+    ///
+    /// `peek_nth_byte_pos(0)` is the current byte position. Returns `None` if fewer than `n`
+    /// characters remain.
+    pub fn peek_nth_byte_pos(&self, n: usize) -> Option<usize> {
+        // Cloning a [`Chars`] iterator is cheap.
+        let mut chars = self.chars.clone();
+        for _ in 0..n {
+            chars.next()?;
+        }
+        Some(self.byte_pos + self.chars.as_str().len() - chars.as_str().len())
+    }
+
+    /// Whether the remaining input starts with `s`, without advancing the cursor.
+    pub fn starts_with(&self, s: &str) -> bool {
+        self.chars.as_str().starts_with(s)
+    }
+
+    /// The slice of the input from a previously saved `start` byte position to the current byte
+    /// position.
+    ///
+    /// # Panics
+    /// Panics if `start` is after the current byte position or does not fall on a UTF-8 char
+    /// boundary of the input string.
+    pub fn slice(&self, start: usize) -> &'a str {
+        match self.checked_slice(start) {
+            Some(slice) => slice,
+            None => panic!("byte position {start} is not a char boundary of the consumed input"),
+        }
+    }
+
+    /// Like [`Cursor::slice`], but returns `None` instead of panicking.
+    pub fn checked_slice(&self, start: usize) -> Option<&'a str> {
+        let consumed = self.consumed();
+        self.input_index(start)
+            .filter(|&index| index <= consumed.len())
+            .map(|index| &consumed[index..])
+    }
+
+    /// A new [`Cursor`] over the `span` of the input, which reports byte positions relative to
+    /// the same document as this cursor.
+    ///
+    /// This is useful to recursively lex a delimited region. The new cursor's lines and columns
+    /// start over from line 1. Use [`Cursor::new_at`] with [`Cursor::position_at`] to keep them.
+    ///
+    /// # Panics
+    /// Panics if `span` is reversed, out of bounds, or its ends do not fall on UTF-8 char
+    /// boundaries of the input string.
+    pub fn subcursor(&self, span: Span) -> Self {
+        self.derive(
+            self.span_str(span),
+            Position {
+                byte: span.start,
+                line: 1,
+                column: 0,
+            },
+        )
+    }
+
+    /// The previous character, i.e. the last consumed one, or `None` at the start of the input.
+    pub fn prev(&self) -> Option<char> {
+        self.consumed().chars().next_back()
+    }
+
+    /// The characters just behind and just ahead of the cursor, i.e. `(self.prev(), self.peek())`.
+    ///
+    /// This is the context needed to detect boundaries, e.g. to split `camelCase` words.
+    pub fn peek_pair(&self) -> (Option<char>, Option<char>) {
+        (self.prev(), self.peek())
+    }
+
+    /// The character starting at `byte_pos` in the input string, whether it was consumed or not,
+    /// without moving the cursor.
+    ///
+    /// Returns `None` if `byte_pos` is out of bounds, at the end of the input, or doesn't fall on a
+    /// UTF-8 char boundary of the input string.
+    pub fn char_at(&self, byte_pos: usize) -> Option<char> {
+        let index = self.input_index(byte_pos)?;
+        self.input[index..].chars().next()
+    }
+
+    /// Finds the byte position of the next occurrence of `needle` in the remaining input, without
+    /// advancing the cursor.
+    pub fn find_byte_pos(&self, needle: char) -> Option<usize> {
+        self.chars
+            .as_str()
+            .find(needle)
+            .map(|index| self.byte_pos + index)
+    }
+
+    /// Counts the run of upcoming characters for which `predicate` is true, without advancing the
+    /// cursor.
+    ///
+    /// This returns the same count [`Cursor::count_while`] would, but leaves the cursor untouched.
+    pub fn peek_while_count<F: FnMut(char) -> bool>(&self, mut predicate: F) -> usize {
+        // Cloning a [`Chars`] iterator is cheap.
+        self.chars.clone().take_while(|&c| predicate(c)).count()
+    }
+
+    /// Peeks the next `n` characters as a slice of the input, without advancing the cursor.
+    ///
+    /// If fewer than `n` characters remain, the whole remaining input is returned.
+    pub fn peek_str(&self, n: usize) -> &'a str {
+        let remaining = self.chars.as_str();
+        // Cloning a [`Chars`] iterator is cheap.
+        let mut chars = self.chars.clone();
+        chars.by_ref().take(n).for_each(drop);
+        &remaining[..remaining.len() - chars.as_str().len()]
+    }
+
+    /// Whether the remaining input is entirely ASCII.
+    ///
+    /// This is O(n), but [`str::is_ascii`] is fast.
+    pub fn is_remaining_ascii(&self) -> bool {
+        self.chars.as_str().is_ascii()
+    }
+
+    /// Finds the byte position of the first non-ASCII byte in the remaining input, without
+    /// advancing the cursor.
+    ///
+    /// This is O(n) in the distance to that byte.
+    pub fn find_non_ascii(&self) -> Option<usize> {
+        self.chars
+            .as_str()
+            .bytes()
+            .position(|b| !b.is_ascii())
+            .map(|index| self.byte_pos + index)
+    }
+
+    /// Peeks the run of upcoming characters for which `predicate` is true, without advancing the
+    /// cursor.
+    ///
+    /// This returns the same slice [`Cursor::bump_while`] would, but leaves the cursor untouched.
+    pub fn peek_while<F: FnMut(char) -> bool>(&self, mut predicate: F) -> &'a str {
+        let remaining = self.chars.as_str();
+        // Cloning a [`Chars`] iterator is cheap.
+        let mut chars = self.chars.clone();
+        while chars.clone().next().is_some_and(&mut predicate) {
+            chars.next();
+        }
+        &remaining[..remaining.len() - chars.as_str().len()]
+    }
+
     /// Bumps the cursor and returns the next character.
     pub fn bump(&mut self) -> Option<char> {
         // Bump the character iterator
         let c = self.chars.next();
-        // Bump the byte position
-        self.byte_pos += c.map(char::len_utf8).unwrap_or_default();
+        if let Some(c) = c {
+            // Bump the byte position
+            self.byte_pos += c.len_utf8();
+            // Bump the line and column
+            self.track(c);
+        }
         c
     }
 
     /// Bumps the cursor and returns the next two characters.
     pub fn bump_two(&mut self) -> (Option<char>, Option<char>) {
-        // Bump the character iterator
-        let (c1, c2) = (self.chars.next(), self.chars.next());
+        // Record the remaining input bytes before bumping
+        let start_length = self.chars.as_str().len();
+        // Bump the character iterator twice, tracking lines and columns after each character.
+        // Like in [`Cursor::skip_while`], the byte_pos update is batched.
+        let first = self.chars.next();
+        if let Some(c) = first {
+            self.track(c);
+        }
+        let second = self.chars.next();
+        if let Some(c) = second {
+            self.track(c);
+        }
+        // Bump the byte_pos by how many bytes were consumed
+        self.byte_pos += start_length - self.chars.as_str().len();
+        (first, second)
+    }
+
+    /// Bumps the cursor and returns the next character along with the [`Span`] it occupies.
+    pub fn bump_spanned(&mut self) -> Option<(char, Span)> {
+        let start = self.byte_pos;
+        let c = self.bump()?;
+        Some((c, self.span_from(start)))
+    }
+
+    /// Bumps the cursor if the next character is a digit in the given `radix`, and returns its
+    /// value.
+    ///
+    /// Otherwise, the cursor is left untouched and `None` is returned.
+    ///
+    /// # Panics
+    /// Panics if `radix` is not in the range `2..=36`, even at the end of the input.
+    pub fn bump_digit(&mut self, radix: u32) -> Option<u32> {
+        assert!(
+            (2..=36).contains(&radix),
+            "radix {radix} is not in the range 2..=36"
+        );
+        let digit = self.peek().and_then(|c| c.to_digit(radix));
+        if digit.is_some() {
+            self.bump();
+        }
+        digit
+    }
+
+    /// Bumps the cursor over a maximal run of digits in the given `radix`, and returns their value
+    /// along with the consumed slice of the input.
+    ///
+    /// The value is `None` if it overflows a [`u64`], but the digits are still consumed. Leading
+    /// zeros are allowed. If the next character isn't a digit, the cursor is left untouched and
+    /// `None` is returned.
+    ///
+    /// # Panics
+    /// Panics if `radix` is not in the range `2..=36`, even at the end of the input.
+    pub fn bump_integer(&mut self, radix: u32) -> Option<(Option<u64>, &'a str)> {
+        assert!(
+            (2..=36).contains(&radix),
+            "radix {radix} is not in the range 2..=36"
+        );
+        let mut value = Some(0u64);
+        let digits = self.bump_while(|c| match c.to_digit(radix) {
+            Some(digit) => {
+                value = value
+                    .and_then(|value| value.checked_mul(radix.into()))
+                    .and_then(|value| value.checked_add(digit.into()));
+                true
+            }
+            None => false,
+        });
+        (!digits.is_empty()).then_some((value, digits))
+    }
+
+    /// Bumps the cursor and returns the next character mapped through `f`.
+    ///
+    /// This is equivalent to `cursor.bump().map(f)`.
+    ///
+    /// ```rust
+    /// use simple_cursor::Cursor;
+    ///
+    /// let mut cursor = Cursor::new("7");
+    /// assert_eq!(cursor.bump_map(|c| c.to_digit(10)), Some(Some(7)));
+    /// assert_eq!(cursor.bump_map(|c| c.to_digit(10)), None);
+    /// ```
+    pub fn bump_map<T, F: FnOnce(char) -> T>(&mut self, f: F) -> Option<T> {
+        self.bump().map(f)
+    }
+
+    /// Bumps the cursor and returns the next character, along with the escaped character if the
+    /// former is `escape`.
+    ///
+    /// For example, with `'\\'` as the escape character, `\"` is returned as `('\\', Some('"'))`
+    /// and `a` as `('a', None)`. An escape character at the end of the input is returned as
+    /// `(escape, None)`.
+    pub fn bump_escaped(&mut self, escape: char) -> Option<(char, Option<char>)> {
+        let c = self.bump()?;
+        let escaped = if c == escape { self.bump() } else { None };
+        Some((c, escaped))
+    }
+
+    /// Bumps the cursor and returns the next `N` characters.
+    ///
+    /// Positions past the end of the input are filled with `None`.
+    pub fn bump_array<const N: usize>(&mut self) -> [Option<char>; N] {
+        core::array::from_fn(|_| self.bump())
+    }
+
+    /// Bumps the cursor and returns the next byte, if it is ASCII.
+    ///
+    /// This avoids decoding UTF-8 for ASCII-heavy inputs. Since an ASCII byte is always a whole
+    /// character, this can be freely mixed with the character methods: the cursor never ends up
+    /// in the middle of a character. If the next byte isn't ASCII (or at the end of the input),
+    /// the cursor is left untouched and `None` is returned; use [`Cursor::bump`] to consume the
+    /// multi-byte character instead.
+    pub fn bump_byte(&mut self) -> Option<u8> {
+        let b = *self
+            .chars
+            .as_str()
+            .as_bytes()
+            .first()
+            .filter(|b| b.is_ascii())?;
+        // ASCII bytes are always a whole character
+        self.chars.next();
+        self.byte_pos += 1;
+        self.track(char::from(b));
+        Some(b)
+    }
+
+    /// Moves the cursor back by one character and returns that character.
+    ///
+    /// Repeated calls walk backwards through the input, and return `None` once the start of the
+    /// input is reached. On a [`TrackedCursor`], moving back over a line break or a tab rescans
+    /// the line before it to recompute the column.
+    pub fn bump_back(&mut self) -> Option<char> {
+        let c = self.prev()?;
+        let consumed = self.consumed();
+        let consumed = &consumed[..consumed.len() - c.len_utf8()];
+        let (start_column, tab_width) = (self.start().column, self.tab_width());
+        self.chars = self.input[consumed.len()..].chars();
+        self.byte_pos -= c.len_utf8();
+        self.char_pos -= 1;
+        if let Some(tracking) = self.tracking_mut() {
+            match c {
+                // These characters don't simply advance the column, so it is recomputed from the
+                // start of the line
+                '\n' | '\r' | '\t' => {
+                    // The `\n` of a `\r\n` doesn't start a new line
+                    if c == '\r' || (c == '\n' && !consumed.ends_with('\r')) {
+                        tracking.line -= 1;
+                    }
+                    let (line, column) = match consumed.rfind(['\n', '\r']) {
+                        Some(index) => (&consumed[index + 1..], 0),
+                        None => (consumed, start_column),
+                    };
+                    tracking.column = line.chars().fold(column, |column, c| match c {
+                        '\t' => next_tab_stop(column, tab_width),
+                        _ => column + 1,
+                    });
+                }
+                _ => tracking.column -= 1,
+            }
+        }
+        Some(c)
+    }
+
+    /// Bumps the cursor over the next `n` bytes, e.g. to skip past a match found externally in
+    /// [`Cursor::remaining`].
+    ///
+    /// The line and column are updated accordingly.
+    ///
+    /// # Panics
+    /// Panics if fewer than `n` bytes remain or if `n` bytes would end in the middle of a
+    /// character.
+    pub fn consume_bytes(&mut self, n: usize) {
+        assert!(
+            self.chars.as_str().is_char_boundary(n),
+            "cannot consume {n} bytes: not a char boundary of the remaining input"
+        );
+        self.advance(n);
+    }
 
-        // Bump the byte position
-        self.byte_pos += c1.map(char::len_utf8).unwrap_or_default();
-        self.byte_pos += c2.map(char::len_utf8).unwrap_or_default();
+    /// Runs an external `parser` on the remaining input, bumps the cursor over the number of bytes
+    /// it reports having consumed, and returns the parsed value.
+    ///
+    /// This allows mixing the cursor with parsers working on string slices.
+    ///
+    /// # Panics
+    /// Panics under the same conditions as [`Cursor::consume_bytes`], if the consumed byte count
+    /// is out of bounds or ends in the middle of a character.
+    pub fn run<T, F: FnOnce(&'a str) -> (T, usize)>(&mut self, parser: F) -> T {
+        let (value, len) = parser(self.chars.as_str());
+        self.consume_bytes(len);
+        value
+    }
+
+    /// Bumps the cursor by up to `n` characters and returns the consumed slice of the input.
+    ///
+    /// If fewer than `n` characters remain, all of them are consumed.
+    pub fn bump_n(&mut self, n: usize) -> &'a str {
+        let mut count = 0;
+        self.bump_while(|_| {
+            count += 1;
+            count <= n
+        })
+    }
+
+    /// Bumps the cursor and returns the next character if it matches `predicate`.
+    ///
+    /// Otherwise, the cursor is left untouched and `None` is returned.
+    pub fn bump_if<F: FnMut(char) -> bool>(&mut self, mut predicate: F) -> Option<char> {
+        match self.peek() {
+            Some(c) if predicate(c) => self.bump(),
+            _ => None,
+        }
+    }
+
+    /// Bumps the cursor if the next character is `expected`, returning whether it matched.
+    pub fn eat(&mut self, expected: char) -> bool {
+        self.bump_if(|c| c == expected).is_some()
+    }
+
+    /// Bumps the cursor and returns the next character if it falls within `range`.
+    ///
+    /// Characters are compared by scalar value. Otherwise, the cursor is left untouched and `None`
+    /// is returned.
+    pub fn eat_in_range(&mut self, range: RangeInclusive<char>) -> Option<char> {
+        self.bump_if(|c| range.contains(&c))
+    }
+
+    /// Bumps the cursor if the next character is `expected`, or returns an [`ExpectError`]
+    /// describing what was found instead.
+    ///
+    /// Nothing is consumed on a mismatch.
+    pub fn expect(&mut self, expected: char) -> Result<(), ExpectError> {
+        if self.eat(expected) {
+            Ok(())
+        } else {
+            Err(ExpectError {
+                expected,
+                found: self.peek(),
+                byte_pos: self.byte_pos,
+            })
+        }
+    }
+
+    /// Bumps the cursor past `s` if the remaining input starts with it, returning whether it
+    /// matched.
+    ///
+    /// Nothing is consumed on a mismatch.
+    pub fn eat_str(&mut self, s: &str) -> bool {
+        let matched = self.starts_with(s);
+        if matched {
+            self.advance(s.len());
+        }
+        matched
+    }
+
+    /// Bumps the cursor past `s` if the remaining input starts with it, or returns an
+    /// [`UnexpectedSequence`] describing what was found instead.
+    ///
+    /// This is the [`Result`]-returning sibling of [`Cursor::eat_str`]. Nothing is consumed on a
+    /// mismatch.
+    pub fn consume_exact<'s>(&mut self, s: &'s str) -> Result<(), UnexpectedSequence<'s>>
+    where
+        'a: 's,
+    {
+        if self.eat_str(s) {
+            Ok(())
+        } else {
+            Err(UnexpectedSequence {
+                expected: s,
+                found: self.peek_str(s.chars().count()),
+                byte_pos: self.byte_pos,
+            })
+        }
+    }
+
+    /// Bumps the cursor past `open`, the content up to the next `close`, and `close` itself, and
+    /// returns the content in between.
+    ///
+    /// Delimiters don't nest: the first `close` ends the content. If the next character isn't
+    /// `open`, or if the input ends before `close` is found, nothing is consumed and an
+    /// [`UnterminatedDelimiter`] is returned.
+    pub fn bump_delimited(
+        &mut self,
+        open: char,
+        close: char,
+    ) -> Result<&'a str, UnterminatedDelimiter> {
+        let checkpoint = self.checkpoint();
+        let start = self.byte_pos;
+        let error = |span| UnterminatedDelimiter { open, close, span };
+        if !self.eat(open) {
+            return Err(error(self.span_from(start)));
+        }
+        let content = self.bump_while(|c| c != close);
+        if self.eat(close) {
+            Ok(content)
+        } else {
+            self.restore(checkpoint);
+            Err(error(Span {
+                start,
+                end: start + open.len_utf8(),
+            }))
+        }
+    }
 
-        (c1, c2)
+    /// Like [`Cursor::bump_delimited`], but delimiters nest: the content ends at the `close`
+    /// matching the initial `open`.
+    ///
+    /// If the next character isn't `open`, or if the input ends before the matching `close` is
+    /// found, nothing is consumed and an [`UnbalancedDelimiter`] is returned. If `open` and
+    /// `close` are the same character, they can't nest, so this behaves like
+    /// [`Cursor::bump_delimited`].
+    pub fn bump_balanced(
+        &mut self,
+        open: char,
+        close: char,
+    ) -> Result<&'a str, UnbalancedDelimiter> {
+        let checkpoint = self.checkpoint();
+        let start = self.byte_pos;
+        let error = |span| UnbalancedDelimiter { open, close, span };
+        if !self.eat(open) {
+            return Err(error(self.span_from(start)));
+        }
+        let mut depth = 1_usize;
+        let content = self.bump_while(|c| {
+            // Checked first, so that identical delimiters close instead of nesting
+            if c == close {
+                depth -= 1;
+            } else if c == open {
+                depth += 1;
+            }
+            depth > 0
+        });
+        if self.eat(close) {
+            Ok(content)
+        } else {
+            self.restore(checkpoint);
+            Err(error(Span {
+                start,
+                end: start + open.len_utf8(),
+            }))
+        }
+    }
+
+    /// Bumps the cursor past the longest of `candidates` the remaining input starts with and
+    /// returns the consumed slice of the input.
+    ///
+    /// This implements maximal munch: given `[">", ">=", ">>"]` and the input `">>="`, `">>"` is
+    /// consumed. If no candidate matches, nothing is consumed and `None` is returned.
+    pub fn eat_longest(&mut self, candidates: &[&str]) -> Option<&'a str> {
+        let remaining = self.chars.as_str();
+        let len = candidates
+            .iter()
+            .filter(|candidate| remaining.starts_with(*candidate))
+            .map(|candidate| candidate.len())
+            .max()?;
+        self.advance(len);
+        Some(&remaining[..len])
+    }
+
+    /// Like [`Cursor::eat_str`], but compares `s` to the remaining input using ASCII
+    /// case-insensitive matching.
+    ///
+    /// Only ASCII letters are case-folded: non-ASCII characters must match exactly.
+    pub fn eat_str_ignore_ascii_case(&mut self, s: &str) -> bool {
+        let matched = self
+            .chars
+            .as_str()
+            .as_bytes()
+            .get(..s.len())
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case(s.as_bytes()));
+        if matched {
+            // ASCII case folding preserves byte lengths, so the matched input prefix is exactly
+            // as long as `s` and ends on a char boundary.
+            self.advance(s.len());
+        }
+        matched
+    }
+
+    /// Bumps the cursor up to (but not including) the next occurrence of `terminator` and returns
+    /// the consumed slice of the input.
+    ///
+    /// If `terminator` isn't found, the rest of the input is consumed.
+    pub fn bump_until_str(&mut self, terminator: &str) -> &'a str {
+        let remaining = self.chars.as_str();
+        let len = remaining.find(terminator).unwrap_or(remaining.len());
+        self.advance(len);
+        &remaining[..len]
     }
 
     /// Bumps the cursor while `predicate` is true for the current character.
@@ -106,94 +1010,3138 @@ impl<'a> Cursor<'a> {
     /// Notably, this method will **not** consume the first non-matching character. This is in
     /// contrast with methods like [`Iterator::take_while`]. This behavior is achieved by peeking
     /// the next character to see if it matches before consuming it.
-    pub fn skip_while(&mut self, predicate: fn(char) -> bool) {
+    pub fn skip_while<F: FnMut(char) -> bool>(&mut self, mut predicate: F) {
         // Record the remaining input bytes before skipping
         let start_length = self.chars.as_str().len();
         // Skip while predicate matches (without taking the first non-matching)
-        while matches!(self.peek(), Some(c) if predicate(c)) {
+        while let Some(c) = self.peek().filter(|&c| predicate(c)) {
             // Notice how this doesn't call [`Cursor::next`] directly.
             // This way we can batch the byte_pos update.
             self.chars.next();
+            self.track(c);
         }
         // Record the remaining input bytes after skipping
         let final_length = self.chars.as_str().len();
         // Bump the byte_pos by how many bytes were skipped
         self.byte_pos += start_length - final_length;
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::Cursor;
-
-    #[test]
-    fn peek() {
-        let input = "s";
-        let cursor = Cursor::new(input);
-        assert_eq!(cursor.peek(), Some('s'));
-        assert_eq!(cursor.byte_pos, 0);
-        assert_eq!(cursor.chars.as_str(), input);
 
-        let input = "";
-        let cursor = Cursor::new(input);
-        assert_eq!(cursor.peek(), None);
-        assert_eq!(cursor.byte_pos, 0);
-        assert_eq!(cursor.chars.as_str(), input);
+    /// Like [`Cursor::skip_while`], but returns whether at least one character was consumed.
+    pub fn skip_while_nonempty<F: FnMut(char) -> bool>(&mut self, predicate: F) -> bool {
+        let start_length = self.chars.as_str().len();
+        self.skip_while(predicate);
+        self.chars.as_str().len() < start_length
     }
 
-    #[test]
+    /// Bumps the cursor while `predicate` is true for the current byte, which must also be ASCII.
+    ///
+    /// Unlike [`Cursor::skip_while`], the input isn't decoded as UTF-8: the skipped run is found
+    /// with a plain byte scan and the cursor is then advanced over it at once. Skipping always
+    /// stops at the first non-ASCII byte, so multi-byte characters are never split.
+    pub fn skip_while_byte<F: FnMut(u8) -> bool>(&mut self, mut predicate: F) {
+        let remaining = self.chars.as_str();
+        let len = remaining
+            .bytes()
+            .position(|b| !(b.is_ascii() && predicate(b)))
+            .unwrap_or(remaining.len());
+        if TRACK {
+            // ASCII bytes are always a whole character
+            let skipped = &remaining.as_bytes()[..len];
+            // A `\r\n` is a single line break, even if its `\r` was consumed before
+            let crlf = skipped.windows(2).filter(|pair| pair == b"\r\n").count()
+                + usize::from(skipped.first() == Some(&b'\n') && self.consumed().ends_with('\r'));
+            let breaks = skipped
+                .iter()
+                .filter(|&&b| b == b'\n' || b == b'\r')
+                .count()
+                - crlf;
+            let tab_width = self.tab_width();
+            if let Some(tracking) = self.tracking_mut() {
+                tracking.line += breaks as u32;
+                // The column only depends on what follows the last line break
+                let (tail, column) = match skipped.iter().rposition(|&b| b == b'\n' || b == b'\r') {
+                    Some(index) => (&skipped[index + 1..], 0),
+                    None => (skipped, tracking.column),
+                };
+                tracking.column = tail.iter().fold(column, |column, &b| match b {
+                    b'\t' => next_tab_stop(column, tab_width),
+                    _ => column + 1,
+                });
+            }
+        }
+        self.chars = remaining[len..].chars();
+        self.byte_pos += len;
+        // ASCII bytes are always a whole character
+        self.char_pos += len;
+    }
+
+    /// Bumps the cursor until `predicate` is true for the current character.
+    ///
+    /// This is the inverse of [`Cursor::skip_while`]: the first matching character is **not**
+    /// consumed.
+    pub fn skip_until<F: FnMut(char) -> bool>(&mut self, mut predicate: F) {
+        self.skip_while(|c| !predicate(c));
+    }
+
+    /// Bumps the cursor through the end of the current line, including the line break.
+    ///
+    /// Like for [`Cursor::line`], `\n`, `\r\n` and a lone `\r` are all line breaks. If there is no
+    /// line break, the rest of the input is consumed.
+    pub fn skip_line(&mut self) {
+        self.skip_until(|c| c == '\n' || c == '\r');
+        if !self.eat_str("\r\n") {
+            self.bump();
+        }
+    }
+
+    /// Bumps the cursor while `predicate` is true for the current character and returns how many
+    /// characters were consumed.
+    ///
+    /// Like [`Cursor::skip_while`], this method will **not** consume the first non-matching
+    /// character.
+    pub fn count_while<F: FnMut(char) -> bool>(&mut self, mut predicate: F) -> usize {
+        let mut count = 0;
+        self.skip_while(|c| {
+            let matched = predicate(c);
+            count += usize::from(matched);
+            matched
+        });
+        count
+    }
+
+    /// Like [`Cursor::count_while`], but consumes at most `max` characters.
+    ///
+    /// This is useful for bounded scans, e.g. at most 6 hex digits in a `\u{...}` escape.
+    /// `predicate` isn't called again once `max` characters have been consumed.
+    pub fn skip_while_max<F: FnMut(char) -> bool>(
+        &mut self,
+        max: usize,
+        mut predicate: F,
+    ) -> usize {
+        let mut count = 0;
+        self.skip_while(|c| {
+            let matched = count < max && predicate(c);
+            count += usize::from(matched);
+            matched
+        });
+        count
+    }
+
+    /// Bumps the cursor while `predicate` is true for the current character and returns the
+    /// consumed slice of the input.
+    ///
+    /// Like [`Cursor::skip_while`], this method will **not** consume the first non-matching
+    /// character.
+    pub fn bump_while<F: FnMut(char) -> bool>(&mut self, predicate: F) -> &'a str {
+        // Record the remaining input before skipping
+        let start = self.chars.as_str();
+        self.skip_while(predicate);
+        // Slice out whatever was skipped
+        &start[..start.len() - self.chars.as_str().len()]
+    }
+
+    /// Bumps the cursor while `f` returns [`ControlFlow::Continue`] for the current character and
+    /// returns the consumed slice of the input.
+    ///
+    /// If `f` returns [`ControlFlow::Break`], the breaking character is **not** consumed and the
+    /// break value is returned along with the slice. Otherwise, the whole remaining input is
+    /// consumed and `None` is returned.
+    pub fn bump_while_cf<B, F: FnMut(char) -> ControlFlow<B>>(
+        &mut self,
+        mut f: F,
+    ) -> (&'a str, Option<B>) {
+        let mut value = None;
+        let consumed = self.bump_while(|c| match f(c) {
+            ControlFlow::Continue(()) => true,
+            ControlFlow::Break(b) => {
+                value = Some(b);
+                false
+            }
+        });
+        (consumed, value)
+    }
+
+    /// Bumps the cursor while `f` returns [`ControlFlow::Continue`] for the current character,
+    /// folding the consumed characters into an accumulator starting at `init`.
+    ///
+    /// If `f` returns [`ControlFlow::Break`], the breaking character is **not** consumed and the
+    /// break value is returned. Otherwise, the whole remaining input is consumed and the final
+    /// accumulator is returned.
+    ///
+    /// ```rust
+    /// use core::ops::ControlFlow;
+    /// use simple_cursor::Cursor;
+    ///
+    /// let mut cursor = Cursor::new("123+4");
+    /// let sum = cursor.fold_while(0, |sum, c| match c.to_digit(10) {
+    ///     Some(digit) => ControlFlow::Continue(sum + digit),
+    ///     None => ControlFlow::Break(sum),
+    /// });
+    /// assert_eq!(sum, 6);
+    /// assert_eq!(cursor.remaining(), "+4");
+    /// ```
+    pub fn fold_while<B, F: FnMut(B, char) -> ControlFlow<B, B>>(
+        &mut self,
+        init: B,
+        mut f: F,
+    ) -> B {
+        let mut accumulator = init;
+        while let Some(c) = self.peek() {
+            match f(accumulator, c) {
+                ControlFlow::Continue(value) => {
+                    accumulator = value;
+                    self.bump();
+                }
+                ControlFlow::Break(value) => return value,
+            }
+        }
+        accumulator
+    }
+
+    /// Like [`Cursor::bump_while`], but also returns how many characters were consumed.
+    pub fn bump_while_counted<F: FnMut(char) -> bool>(&mut self, predicate: F) -> (&'a str, usize) {
+        // Record the remaining input before skipping
+        let start = self.chars.as_str();
+        let count = self.count_while(predicate);
+        // Slice out whatever was skipped
+        (&start[..start.len() - self.chars.as_str().len()], count)
+    }
+
+    /// Like [`Cursor::bump_while`], but `predicate` also receives the 0-based index of the
+    /// current character within the run.
+    pub fn bump_while_indexed<F: FnMut(usize, char) -> bool>(
+        &mut self,
+        mut predicate: F,
+    ) -> &'a str {
+        let mut index = 0;
+        self.bump_while(|c| {
+            let matched = predicate(index, c);
+            index += 1;
+            matched
+        })
+    }
+
+    /// Bumps the cursor past a character matching `head` followed by any characters matching
+    /// `tail`, and returns the consumed slice of the input.
+    ///
+    /// This captures the usual identifier shape, e.g. an alphabetic character followed by
+    /// alphanumeric ones. If the next character doesn't match `head`, nothing is consumed and
+    /// `None` is returned.
+    pub fn bump_ident<H: FnMut(char) -> bool, T: FnMut(char) -> bool>(
+        &mut self,
+        head: H,
+        tail: T,
+    ) -> Option<&'a str> {
+        // Record the remaining input before skipping
+        let start = self.chars.as_str();
+        self.bump_if(head)?;
+        self.skip_while(tail);
+        // Slice out whatever was skipped
+        Some(&start[..start.len() - self.chars.as_str().len()])
+    }
+
+    /// Bumps the cursor while the current character falls within any of `ranges` and returns the
+    /// consumed slice of the input.
+    ///
+    /// `ranges` is binary searched, which is efficient for large character class tables. It must
+    /// be sorted and its ranges must not overlap, otherwise matching characters may be missed.
+    pub fn bump_while_ranges(&mut self, ranges: &[RangeInclusive<char>]) -> &'a str {
+        self.bump_while(|c| {
+            ranges
+                .binary_search_by(|range| {
+                    if *range.end() < c {
+                        Ordering::Less
+                    } else if *range.start() > c {
+                        Ordering::Greater
+                    } else {
+                        Ordering::Equal
+                    }
+                })
+                .is_ok()
+        })
+    }
+
+    /// Bumps the cursor past the byte-order mark (`U+FEFF`) at the start of the input, if any.
+    ///
+    /// This does nothing if the cursor isn't at byte position 0, e.g. if it has already advanced or
+    /// if it was created with [`Cursor::with_offset`] for a fragment in the middle of a document.
+    pub fn skip_bom(&mut self) {
+        if self.byte_pos == 0 {
+            self.eat('\u{FEFF}');
+        }
+    }
+
+    /// Bumps the cursor while the current character is whitespace, as defined by
+    /// [`char::is_whitespace`].
+    pub fn skip_whitespace(&mut self) {
+        self.skip_while(char::is_whitespace);
+    }
+
+    /// Like [`Cursor::skip_whitespace`], but returns the [`Span`] of the skipped whitespace.
+    ///
+    /// If there is no whitespace, the span is empty and starts at the current byte position.
+    pub fn skip_whitespace_spanned(&mut self) -> Span {
+        let start = self.byte_pos;
+        self.skip_whitespace();
+        self.span_from(start)
+    }
+
+    /// Bumps the cursor while the current character is ASCII whitespace, as defined by
+    /// [`char::is_ascii_whitespace`].
+    pub fn skip_ascii_whitespace(&mut self) {
+        self.skip_while(|c| c.is_ascii_whitespace());
+    }
+
+    /// Runs `f` on the cursor and returns its result along with the byte range it consumed.
+    pub fn spanned<T, F: FnOnce(&mut Self) -> T>(&mut self, f: F) -> (T, Range<usize>) {
+        let start = self.byte_pos;
+        let value = f(self);
+        (value, start..self.byte_pos)
+    }
+
+    /// Creates a [`Span`] from a previously saved `start` byte position to the current byte
+    /// position.
+    pub fn span_from(&self, start: usize) -> Span {
+        Span {
+            start,
+            end: self.byte_pos,
+        }
+    }
+
+    /// Iterates over the remaining characters, bumping the cursor for each of them.
+    ///
+    /// This allows using the cursor with [`Iterator`] adapters. The cursor itself doesn't
+    /// implement [`Iterator`], since methods like [`Iterator::find`] or [`Iterator::position`]
+    /// would then shadow the inherent methods of the same name.
+    pub fn iter(&mut self) -> impl Iterator<Item = char> + use<'_, 'a, TRACK> {
+        Iter(self)
+    }
+
+    /// Turns the cursor into a lazy stream of tokens produced by repeatedly calling `f`, until it
+    /// returns `None`.
+    ///
+    /// `f` is responsible for making progress: if it returns `Some` without advancing the cursor,
+    /// it will be called again in the same state, likely producing an infinite stream.
+    pub fn tokenize<T, F: FnMut(&mut Self) -> Option<T>>(
+        mut self,
+        mut f: F,
+    ) -> impl Iterator<Item = T> + use<'a, TRACK, T, F> {
+        core::iter::from_fn(move || f(&mut self))
+    }
+
+    /// Turns the cursor into an iterator over the whitespace-delimited words of the remaining
+    /// input, along with their [`Span`].
+    ///
+    /// Like [`str::split_whitespace`], whitespace is defined by [`char::is_whitespace`] and no
+    /// empty words are yielded.
+    pub fn words(self) -> impl Iterator<Item = (Span, &'a str)> {
+        self.tokenize(|cursor| {
+            cursor.skip_whitespace();
+            let start = cursor.byte_pos;
+            let word = cursor.bump_while(|c| !c.is_whitespace());
+            (!word.is_empty()).then(|| (cursor.span_from(start), word))
+        })
+    }
+
+    /// Repeatedly calls `rule` to lex a token and passes its [`Span`] and slice of the input to
+    /// `sink`, until `rule` returns `None`.
+    ///
+    /// Unlike [`Cursor::tokenize`], this doesn't consume the cursor and lets `sink` store tokens
+    /// anywhere, e.g. in a fixed-size buffer when allocation isn't available. Lexing also stops
+    /// when `rule` doesn't advance the cursor, since it would otherwise loop forever. The token it
+    /// returned is then not passed to `sink`.
+    ///
+    /// # Panics
+    /// Panics if `rule` returns a span which is reversed, out of bounds, or doesn't fall on
+    /// UTF-8 char boundaries of the input string.
+    pub fn lex_each<R: FnMut(&mut Self) -> Option<Span>, F: FnMut(Span, &'a str)>(
+        &mut self,
+        mut rule: R,
+        mut sink: F,
+    ) {
+        loop {
+            let start = self.byte_pos;
+            let Some(span) = rule(self) else {
+                return;
+            };
+            let token = self.span_str(span);
+            if self.byte_pos == start {
+                return;
+            }
+            sink(span, token);
+        }
+    }
+
+    /// Rewinds the cursor back to the start of the input string.
+    ///
+    /// This also resets the line and column.
+    pub fn reset(&mut self) {
+        self.byte_pos = self.start_byte();
+        self.chars = self.input.chars();
+        self.char_pos = 0;
+        if let Some(tracking) = self.tracking_mut() {
+            tracking.reset();
+        }
+    }
+
+    /// Resets the cursor to a fresh state over a new input string, as if created by
+    /// [`Cursor::new`].
+    ///
+    /// The tab width is kept.
+    pub fn reinit(&mut self, input: &'a str) {
+        *self = self.derive(
+            input,
+            Position {
+                byte: 0,
+                line: 1,
+                column: 0,
+            },
+        );
+    }
+
+    /// Moves the cursor to `byte_pos` in the input string.
+    ///
+    /// The line and column of a [`TrackedCursor`] are updated accordingly, but seeking backwards
+    /// then has to rescan the input from the start.
+    ///
+    /// # Panics
+    /// Panics if `byte_pos` is out of bounds or does not fall on a UTF-8 char boundary of the
+    /// input string, since that would split a character in half.
+    pub fn seek_to(&mut self, byte_pos: usize) {
+        if let Err(error) = self.try_seek_to(byte_pos) {
+            panic!("{error}");
+        }
+    }
+
+    /// Like [`Cursor::seek_to`], but returns an error instead of panicking.
+    pub fn try_seek_to(&mut self, byte_pos: usize) -> Result<(), NotCharBoundary> {
+        if self.input_index(byte_pos).is_none() {
+            return Err(NotCharBoundary { byte_pos });
+        }
+        if !TRACK {
+            // Nothing to track, so the input doesn't need to be rescanned from the start
+            self.jump_to(byte_pos - self.start_byte());
+            return Ok(());
+        }
+        if byte_pos < self.byte_pos {
+            self.reset();
+        }
+        self.advance(byte_pos - self.byte_pos);
+        Ok(())
+    }
+
+    /// Moves the cursor to the nearest char boundary at or before `byte_pos`.
+    ///
+    /// Unlike [`Cursor::seek_to`], this never panics: a position in the middle of a character is
+    /// rounded **down** to the start of that character, and positions outside of the input are
+    /// clamped to its start or end.
+    pub fn seek_to_nearest_boundary(&mut self, byte_pos: usize) {
+        let index = byte_pos
+            .saturating_sub(self.start_byte())
+            .min(self.input.len());
+        // Index 0 is always a char boundary
+        let index = (0..=index)
+            .rev()
+            .find(|&index| self.input.is_char_boundary(index))
+            .unwrap_or_default();
+        self.seek_to(self.start_byte() + index);
+    }
+
+    /// Saves the current state of the cursor, which can later be restored with
+    /// [`Cursor::restore`].
+    pub fn checkpoint(&self) -> Checkpoint<'a, TRACK> {
+        Checkpoint {
+            chars: self.chars.clone(),
+            byte_pos: self.byte_pos,
+            char_pos: self.char_pos,
+            tracking: self.tracking.clone(),
+        }
+    }
+
+    /// Restores a state previously saved by [`Cursor::checkpoint`].
+    ///
+    /// The checkpoint must have been created from this cursor.
+    pub fn restore(&mut self, checkpoint: Checkpoint<'a, TRACK>) {
+        self.chars = checkpoint.chars;
+        self.byte_pos = checkpoint.byte_pos;
+        self.char_pos = checkpoint.char_pos;
+        self.tracking = checkpoint.tracking;
+    }
+
+    /// Runs `f` on the cursor and restores the cursor to its prior state if `f` returns `None`.
+    ///
+    /// This is the basic backtracking primitive for speculative parsing. If `f` returns `Some`,
+    /// whatever it consumed stays consumed. The state is **not** restored if `f` panics.
+    pub fn try_parse<T, F: FnOnce(&mut Self) -> Option<T>>(&mut self, f: F) -> Option<T> {
+        let checkpoint = self.checkpoint();
+        let value = f(self);
+        if value.is_none() {
+            self.restore(checkpoint);
+        }
+        value
+    }
+
+    /// The slice of the input covered by `span`.
+    ///
+    /// # Panics
+    /// Panics if `span` is reversed, out of bounds, or its ends do not fall on UTF-8 char
+    /// boundaries of the input string.
+    fn span_str(&self, span: Span) -> &'a str {
+        let index = |byte_pos| match self.input_index(byte_pos) {
+            Some(index) => index,
+            None => panic!("{}", NotCharBoundary { byte_pos }),
+        };
+        let (start, end) = (index(span.start), index(span.end));
+        assert!(start <= end, "span {span} is reversed");
+        &self.input[start..end]
+    }
+
+    /// Converts `byte_pos` to an index into the input string, if it is a char boundary.
+    fn input_index(&self, byte_pos: usize) -> Option<usize> {
+        byte_pos
+            .checked_sub(self.start_byte())
+            .filter(|&index| self.input.is_char_boundary(index))
+    }
+
+    /// Moves the cursor to `index` in the input string, which must be a char boundary, without
+    /// updating the line and column.
+    fn jump_to(&mut self, index: usize) {
+        let start_byte = self.start_byte();
+        let local = self.consumed().len();
+        // Only count the characters between the two positions
+        if index < local {
+            self.char_pos -= self.input[index..local].chars().count();
+        } else {
+            self.char_pos += self.input[local..index].chars().count();
+        }
+        self.chars = self.input[index..].chars();
+        self.byte_pos = start_byte + index;
+    }
+
+    /// The byte position of the start of the input string.
+    fn start_byte(&self) -> usize {
+        self.byte_pos - self.consumed().len()
+    }
+
+    /// The [`Position`] of the start of the input string.
+    fn start(&self) -> Position {
+        let (line, column) = self.tracking().map_or((1, 0), |tracking| {
+            (tracking.start_line, tracking.start_column)
+        });
+        Position {
+            byte: self.start_byte(),
+            line,
+            column,
+        }
+    }
+
+    /// Column width of a tab character.
+    fn tab_width(&self) -> u32 {
+        self.tracking().map_or(1, |tracking| tracking.tab_width)
+    }
+
+    /// The line and column tracking state, if `TRACK` is set.
+    fn tracking(&self) -> Option<&LineTracking> {
+        Track::<TRACK>::get(&self.tracking)
+    }
+
+    /// Like [`Cursor::tracking`], but mutable.
+    fn tracking_mut(&mut self) -> Option<&mut LineTracking> {
+        Track::<TRACK>::get_mut(&mut self.tracking)
+    }
+
+    /// Bumps the cursor over the next `len` bytes, which must end on a char boundary.
+    fn advance(&mut self, len: usize) {
+        if !TRACK {
+            self.jump_to(self.consumed().len() + len);
+            return;
+        }
+        let end = self.chars.as_str().len() - len;
+        while self.chars.as_str().len() > end {
+            self.bump();
+        }
+    }
+
+    /// Updates the character position after `c` has been consumed, as well as the line and column
+    /// if `TRACK` is set.
+    fn track(&mut self, c: char) {
+        self.char_pos += 1;
+        if !TRACK {
+            return;
+        }
+        let after_cr = c == '\n' && self.consumed().ends_with("\r\n");
+        if let Some(tracking) = self.tracking_mut() {
+            tracking.track(c, after_cr);
+        }
+    }
+}
+
+impl<const TRACK: bool> fmt::Debug for Cursor<'_, TRACK>
+where
+    Track<TRACK>: Tracking,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cursor")
+            .field("byte_pos", &self.byte_pos)
+            .field("remaining", &Truncated(self.chars.as_str()))
+            .finish()
+    }
+}
+
+/// Two cursors are equal if they are at the same byte position and their remaining inputs are
+/// equal. Line, column and character positions are not compared.
+impl<const TRACK: bool> PartialEq for Cursor<'_, TRACK>
+where
+    Track<TRACK>: Tracking,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.byte_pos == other.byte_pos && self.chars.as_str() == other.chars.as_str()
+    }
+}
+
+impl<const TRACK: bool> Eq for Cursor<'_, TRACK> where Track<TRACK>: Tracking {}
+
+/// Cursors are ordered by byte position, which is only meaningful for cursors over the same input.
+///
+/// To stay consistent with [`PartialEq`], cursors at the same byte position but with different
+/// remaining inputs are not comparable.
+impl<const TRACK: bool> PartialOrd for Cursor<'_, TRACK>
+where
+    Track<TRACK>: Tracking,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match self.byte_pos.cmp(&other.byte_pos) {
+            Ordering::Equal => (self == other).then_some(Ordering::Equal),
+            ordering => Some(ordering),
+        }
+    }
+}
+
+/// Debug formatting of a string truncated to a readable length.
+struct Truncated<'a>(&'a str);
+
+impl fmt::Debug for Truncated<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        /// Maximum number of characters to show.
+        const MAX_CHARS: usize = 32;
+        match self.0.char_indices().nth(MAX_CHARS) {
+            Some((end, _)) => write!(f, "{:?}...", &self.0[..end]),
+            None => write!(f, "{:?}", self.0),
+        }
+    }
+}
+
+/// Iterator bumping a cursor, see [`Cursor::iter`].
+struct Iter<'c, 'a, const TRACK: bool>(&'c mut Cursor<'a, TRACK>)
+where
+    Track<TRACK>: Tracking;
+
+impl<const TRACK: bool> Iterator for Iter<'_, '_, TRACK>
+where
+    Track<TRACK>: Tracking,
+{
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        self.0.bump()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Each character takes between 1 and 4 bytes.
+        let len = self.0.chars.as_str().len();
+        (len.div_ceil(4), Some(len))
+    }
+}
+
+/// Snapshot of a location into the input string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    /// Byte position, see [`Cursor::byte_pos`].
+    pub byte: usize,
+    /// Line (1-based), see [`Cursor::line`].
+    pub line: u32,
+    /// Column (0-based), see [`Cursor::column`].
+    pub column: u32,
+}
+
+/// Formats the position as `line:column`.
+///
+/// ```rust
+/// use simple_cursor::Position;
+///
+/// let position = Position { byte: 42, line: 3, column: 15 };
+/// assert_eq!(position.to_string(), "3:15");
+/// ```
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// Range of byte positions into the input string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    /// Start byte position (inclusive).
+    pub start: usize,
+    /// End byte position (exclusive).
+    pub end: usize,
+}
+
+impl Span {
+    /// Length of the span in bytes.
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// Whether the span is empty.
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+/// Formats the span as `start..end`.
+///
+/// ```rust
+/// use simple_cursor::Span;
+///
+/// let span = Span { start: 12, end: 19 };
+/// assert_eq!(span.to_string(), "12..19");
+/// ```
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}..{}", self.start, self.end)
+    }
+}
+
+impl From<Range<usize>> for Span {
+    fn from(range: Range<usize>) -> Self {
+        Self {
+            start: range.start,
+            end: range.end,
+        }
+    }
+}
+
+impl From<Span> for Range<usize> {
+    fn from(span: Span) -> Self {
+        span.start..span.end
+    }
+}
+
+/// Error returned by [`Cursor::expect`] when the next character doesn't match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExpectError {
+    /// The expected character.
+    pub expected: char,
+    /// The character actually found, or `None` at the end of the input.
+    pub found: Option<char>,
+    /// Byte position of the found character.
+    pub byte_pos: usize,
+}
+
+impl fmt::Display for ExpectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "expected {:?}, found ", self.expected)?;
+        match self.found {
+            Some(c) => write!(f, "{c:?}")?,
+            None => write!(f, "end of input")?,
+        }
+        write!(f, " at byte position {}", self.byte_pos)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ExpectError {}
+
+/// Error returned by [`Cursor::consume_exact`] when the remaining input doesn't start with the
+/// expected string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnexpectedSequence<'a> {
+    /// The expected string.
+    pub expected: &'a str,
+    /// The input actually found, as many characters long as `expected` (or shorter at the end of
+    /// the input).
+    pub found: &'a str,
+    /// Byte position of the found input.
+    pub byte_pos: usize,
+}
+
+impl fmt::Display for UnexpectedSequence<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "expected {:?}, found ", self.expected)?;
+        if self.found.is_empty() {
+            write!(f, "end of input")?;
+        } else {
+            write!(f, "{:?}", self.found)?;
+        }
+        write!(f, " at byte position {}", self.byte_pos)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UnexpectedSequence<'_> {}
+
+/// Error returned by [`Cursor::bump_delimited`] when a delimited region isn't closed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnterminatedDelimiter {
+    /// The opening delimiter.
+    pub open: char,
+    /// The expected closing delimiter.
+    pub close: char,
+    /// Span of the opening delimiter, which is empty if the opening delimiter itself was missing.
+    pub span: Span,
+}
+
+impl fmt::Display for UnterminatedDelimiter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.span.is_empty() {
+            write!(f, "expected {:?}", self.open)?;
+        } else {
+            write!(f, "unterminated {:?}, expected {:?}", self.open, self.close)?;
+        }
+        write!(f, " at byte position {}", self.span.start)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UnterminatedDelimiter {}
+
+/// Error returned by [`Cursor::bump_balanced`] when a nested delimited region isn't closed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnbalancedDelimiter {
+    /// The opening delimiter.
+    pub open: char,
+    /// The expected closing delimiter.
+    pub close: char,
+    /// Span of the unmatched opening delimiter, which is empty if the opening delimiter itself
+    /// was missing.
+    pub span: Span,
+}
+
+impl fmt::Display for UnbalancedDelimiter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.span.is_empty() {
+            write!(f, "expected {:?}", self.open)?;
+        } else {
+            write!(f, "unmatched {:?}, expected {:?}", self.open, self.close)?;
+        }
+        write!(f, " at byte position {}", self.span.start)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UnbalancedDelimiter {}
+
+/// Error returned by [`Cursor::expect_eof`] when input remains.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrailingInput<'a> {
+    /// The remaining input.
+    pub remaining: &'a str,
+    /// Byte position of the remaining input.
+    pub byte_pos: usize,
+}
+
+impl fmt::Display for TrailingInput<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "expected end of input, found {:?} at byte position {}",
+            Truncated(self.remaining),
+            self.byte_pos
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TrailingInput<'_> {}
+
+/// Error returned when a byte position is out of bounds or does not fall on a UTF-8 char
+/// boundary of the input string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NotCharBoundary {
+    /// The offending byte position.
+    pub byte_pos: usize,
+}
+
+impl fmt::Display for NotCharBoundary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "byte position {} is not a char boundary of the input",
+            self.byte_pos
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for NotCharBoundary {}
+
+/// Index of the line starts of an input string, see [`Cursor::build_line_index`].
+///
+/// Unlike [`Cursor::position_at`], which scans the input, this finds positions in O(log n) (plus
+/// the length of the line), which is useful for repeated lookups.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone)]
+pub struct LineIndex<'a> {
+    /// Indexed input string.
+    input: &'a str,
+    /// Position of the start of the input string.
+    start: Position,
+    /// Column width of a tab character.
+    tab_width: u32,
+    /// Index into the input of the start of each line after the first.
+    line_starts: Vec<usize>,
+}
+
+#[cfg(feature = "alloc")]
+impl LineIndex<'_> {
+    /// Computes the [`Position`] of `byte` in the input string.
+    ///
+    /// This returns the same position as [`Cursor::position_at`].
+    ///
+    /// # Panics
+    /// Panics if `byte` is out of bounds or does not fall on a UTF-8 char boundary of the input
+    /// string.
+    pub fn position(&self, byte: usize) -> Position {
+        let index = byte
+            .checked_sub(self.start.byte)
+            .filter(|&index| self.input.is_char_boundary(index));
+        let Some(index) = index else {
+            panic!("{}", NotCharBoundary { byte_pos: byte });
+        };
+        // Number of lines started at or before `index`
+        let lines = self.line_starts.partition_point(|&start| start <= index);
+        let (line_start, column) = match lines.checked_sub(1) {
+            Some(line) => (self.line_starts[line], 0),
+            None => (0, self.start.column),
+        };
+        let column = self.input[line_start..index]
+            .chars()
+            .fold(column, |column, c| match c {
+                // Only the `\n` of a `\r\n` line break can be part of a line
+                '\n' => column,
+                '\t' => next_tab_stop(column, self.tab_width),
+                _ => column + 1,
+            });
+        Position {
+            byte,
+            // There can't be more lines than `u32::MAX` since lines are tracked as `u32`
+            line: self.start.line + lines as u32,
+            column,
+        }
+    }
+}
+
+/// Saved state of a [`Cursor`], see [`Cursor::checkpoint`].
+#[derive(Debug, Clone)]
+pub struct Checkpoint<'a, const TRACK: bool = false>
+where
+    Track<TRACK>: Tracking,
+{
+    /// Saved character iterator.
+    chars: Chars<'a>,
+    /// Saved byte position.
+    byte_pos: usize,
+    /// Saved character position.
+    char_pos: usize,
+    /// Saved line and column tracking state.
+    tracking: <Track<TRACK> as Tracking>::State,
+}
+
+/// The column of the tab stop following `column`.
+fn next_tab_stop(column: u32, tab_width: u32) -> u32 {
+    (column / tab_width + 1) * tab_width
+}
+
+/// Line and column tracking state of a [`Cursor`].
+///
+/// The items are public but unnameable outside of this crate, since they appear in the bounds of
+/// [`Cursor`].
+mod tracking {
+    use core::fmt;
+
+    use super::{next_tab_stop, Position};
+
+    /// Selects the tracking state of a [`Cursor`](super::Cursor) from its `TRACK` parameter.
+    pub struct Track<const TRACK: bool>;
+
+    /// Tracking state of a [`Cursor`](super::Cursor).
+    pub trait Tracking {
+        /// The stored state, which is zero-sized if nothing is tracked.
+        type State: Clone + fmt::Debug;
+
+        /// The state of a cursor over an input string which starts at `start`.
+        fn new(start: Position) -> Self::State;
+
+        /// The line and column tracking state, if any.
+        fn get(state: &Self::State) -> Option<&LineTracking>;
+
+        /// Like [`Tracking::get`], but mutable.
+        fn get_mut(state: &mut Self::State) -> Option<&mut LineTracking>;
+    }
+
+    impl Tracking for Track<false> {
+        type State = ();
+
+        fn new(_: Position) {}
+
+        fn get(_: &()) -> Option<&LineTracking> {
+            None
+        }
+
+        fn get_mut(_: &mut ()) -> Option<&mut LineTracking> {
+            None
+        }
+    }
+
+    impl Tracking for Track<true> {
+        type State = LineTracking;
+
+        fn new(start: Position) -> LineTracking {
+            LineTracking {
+                start_line: start.line,
+                start_column: start.column,
+                line: start.line,
+                column: start.column,
+                tab_width: 1,
+            }
+        }
+
+        fn get(state: &LineTracking) -> Option<&LineTracking> {
+            Some(state)
+        }
+
+        fn get_mut(state: &mut LineTracking) -> Option<&mut LineTracking> {
+            Some(state)
+        }
+    }
+
+    /// Line and column of a [`TrackedCursor`](super::TrackedCursor).
+    #[derive(Debug, Clone, Copy)]
+    pub struct LineTracking {
+        /// Line of the start of the input string.
+        pub(crate) start_line: u32,
+        /// Column of the start of the input string.
+        pub(crate) start_column: u32,
+        /// Current line of the cursor (1-based).
+        pub(crate) line: u32,
+        /// Current column of the cursor (0-based), in characters.
+        pub(crate) column: u32,
+        /// Column width of a tab character.
+        pub(crate) tab_width: u32,
+    }
+
+    impl LineTracking {
+        /// Goes back to the start of the input string.
+        pub(crate) fn reset(&mut self) {
+            self.line = self.start_line;
+            self.column = self.start_column;
+        }
+
+        /// Updates the state after `c` has been consumed. `after_cr` tells whether `c` is the
+        /// `\n` of a `\r\n`.
+        pub(crate) fn track(&mut self, c: char, after_cr: bool) {
+            match c {
+                // The line break was already counted when the `\r` was consumed
+                '\n' if after_cr => {}
+                '\n' | '\r' => {
+                    self.line += 1;
+                    self.column = 0;
+                }
+                // Advance to the next tab stop
+                '\t' => self.column = next_tab_stop(self.column, self.tab_width),
+                _ => self.column += 1,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::{
+        Checkpoint, Cursor, ExpectError, NotCharBoundary, Position, Span, TrackedCursor,
+        TrailingInput, UnbalancedDelimiter, UnexpectedSequence, UnterminatedDelimiter,
+    };
+    use core::mem::size_of;
+    use core::ops::{ControlFlow, Range};
+    use core::str::Chars;
+    use std::format;
+
+    #[test]
+    fn peek() {
+        let input = "s";
+        let cursor = Cursor::new(input);
+        assert_eq!(cursor.peek(), Some('s'));
+        assert_eq!(cursor.byte_pos, 0);
+        assert_eq!(cursor.chars.as_str(), input);
+
+        let input = "";
+        let cursor = Cursor::new(input);
+        assert_eq!(cursor.peek(), None);
+        assert_eq!(cursor.byte_pos, 0);
+        assert_eq!(cursor.chars.as_str(), input);
+    }
+
+    #[test]
     fn peek_two() {
         let input = "ab";
         let cursor = Cursor::new(input);
-        assert_eq!(cursor.peek_two(), (Some('a'), Some('b')));
+        assert_eq!(cursor.peek_two(), (Some('a'), Some('b')));
+        assert_eq!(cursor.byte_pos, 0);
+        assert_eq!(cursor.chars.as_str(), input);
+
+        let input = "a";
+        let cursor = Cursor::new(input);
+        assert_eq!(cursor.peek_two(), (Some('a'), None));
+        assert_eq!(cursor.byte_pos, 0);
+        assert_eq!(cursor.chars.as_str(), input);
+
+        let input = "";
+        let cursor = Cursor::new(input);
+        assert_eq!(cursor.peek_two(), (None, None));
+        assert_eq!(cursor.byte_pos, 0);
+        assert_eq!(cursor.chars.as_str(), input);
+    }
+
+    #[test]
+    fn peek_nth() {
+        let input = "..=";
+        let cursor = Cursor::new(input);
+        assert_eq!(cursor.peek_nth(0), cursor.peek());
+        assert_eq!(cursor.peek_nth(1), Some('.'));
+        assert_eq!(cursor.peek_nth(2), Some('='));
+        assert_eq!(cursor.peek_nth(3), None);
+        assert_eq!(cursor.byte_pos, 0);
+        assert_eq!(cursor.chars.as_str(), input);
+
+        let input = "";
+        let cursor = Cursor::new(input);
+        assert_eq!(cursor.peek_nth(0), None);
+    }
+
+    #[test]
+    fn bump() {
+        let input = "a";
+        let mut cursor = Cursor::new(input);
+        assert_eq!(cursor.bump(), Some('a'));
+        assert_eq!(cursor.byte_pos, 1);
+        assert_eq!(cursor.chars.as_str(), "");
+    }
+
+    #[test]
+    fn bump_two() {
+        let input = "abc";
+        let mut cursor = Cursor::new(input);
+        assert_eq!(cursor.bump_two(), (Some('a'), Some('b')));
+        assert_eq!(cursor.byte_pos, 2);
+        assert_eq!(cursor.chars.as_str(), "c");
+
+        assert_eq!(cursor.bump_two(), (Some('c'), None));
+        assert_eq!(cursor.byte_pos, 3);
+        assert_eq!(cursor.chars.as_str(), "");
+
+        assert_eq!(cursor.bump_two(), (None, None));
+        assert_eq!(cursor.byte_pos, 3);
+        assert_eq!(cursor.chars.as_str(), "");
+    }
+
+    #[test]
+    fn bump_two_matches_bump() {
+        let input = "竜\r\n\té\n\rx";
+        let mut expected = Cursor::new(input).tracked();
+        let mut cursor = Cursor::new(input).tracked();
+        while !cursor.is_eof() {
+            assert_eq!(cursor.bump_two(), (expected.bump(), expected.bump()));
+            assert_eq!(cursor.byte_pos, expected.byte_pos);
+            assert_eq!(cursor.char_pos(), expected.char_pos());
+            assert_eq!(cursor.position(), expected.position());
+        }
+    }
+
+    #[test]
+    fn skip_while() {
+        let input = "aaaab";
+        let mut cursor = Cursor::new(input);
+        cursor.skip_while(|c| c == 'a');
+        assert_eq!(cursor.byte_pos, 4);
+        assert_eq!(cursor.chars.as_str(), "b");
+
+        let input = "a,b;c";
+        let delimiters = [',', ';'];
+        let mut skipped = 0;
+        let mut cursor = Cursor::new(input);
+        cursor.skip_while(|c| {
+            skipped += 1;
+            !delimiters.contains(&c)
+        });
+        assert_eq!(skipped, 2);
+        assert_eq!(cursor.byte_pos, 1);
+        assert_eq!(cursor.chars.as_str(), ",b;c");
+    }
+
+    #[test]
+    fn bump_while() {
+        let input = "foo竜 bar";
+        let mut cursor = Cursor::new(input);
+        assert_eq!(cursor.bump_while(|c| !c.is_whitespace()), "foo竜");
+        assert_eq!(cursor.byte_pos, 6);
+        assert_eq!(cursor.chars.as_str(), " bar");
+
+        assert_eq!(cursor.bump_while(char::is_alphabetic), "");
+        assert_eq!(cursor.byte_pos, 6);
+        assert_eq!(cursor.chars.as_str(), " bar");
+    }
+
+    #[test]
+    fn remaining() {
+        let input = "ab竜c";
+        let mut cursor = Cursor::new(input);
+        assert_eq!(cursor.remaining(), input);
+
+        cursor.bump_two();
+        assert_eq!(cursor.remaining(), "竜c");
+        assert_eq!(cursor.remaining().len(), input.len() - cursor.byte_pos());
+        assert_eq!(cursor.byte_pos, 2);
+    }
+
+    #[test]
+    fn consumed() {
+        let input = "ab竜c";
+        let mut cursor = Cursor::new(input);
+        assert_eq!(cursor.consumed(), "");
+
+        cursor.bump_two();
+        cursor.bump();
+        assert_eq!(cursor.consumed(), "ab竜");
+        assert_eq!(cursor.remaining(), "c");
+        assert_eq!(cursor.consumed().len(), cursor.byte_pos);
+    }
+
+    #[test]
+    fn line_column() {
+        let input = "a竜\nbc\r\nd";
+        let mut cursor = Cursor::new(input).tracked();
+        assert_eq!((cursor.line(), cursor.column()), (1, 0));
+
+        cursor.bump_two();
+        assert_eq!((cursor.line(), cursor.column()), (1, 2));
+
+        cursor.bump();
+        assert_eq!((cursor.line(), cursor.column()), (2, 0));
+
+        cursor.skip_while(|c| c != 'd');
+        assert_eq!((cursor.line(), cursor.column()), (3, 0));
+
+        cursor.bump();
+        assert_eq!((cursor.line(), cursor.column()), (3, 1));
+        assert_eq!(cursor.byte_pos, input.len());
+    }
+
+    #[test]
+    fn position() {
+        let input = "ab\n竜c";
+        let mut cursor = Cursor::new(input).tracked();
+        assert_eq!(
+            cursor.position(),
+            Position {
+                byte: 0,
+                line: 1,
+                column: 0
+            }
+        );
+
+        cursor.skip_while(|c| c != 'c');
+        assert_eq!(
+            cursor.position(),
+            Position {
+                byte: 6,
+                line: 2,
+                column: 1
+            }
+        );
+    }
+
+    #[test]
+    fn reset() {
+        let input = "ab\ncd";
+        let mut cursor = Cursor::new(input).tracked();
+        cursor.skip_while(|c| c != 'd');
+        cursor.reset();
+        assert_eq!(cursor.byte_pos, 0);
+        assert_eq!(cursor.chars.as_str(), input);
+        assert_eq!((cursor.line(), cursor.column()), (1, 0));
+    }
+
+    #[test]
+    fn seek_to() {
+        let input = "ab\n竜d";
+        let mut cursor = Cursor::new(input).tracked();
+        cursor.seek_to(6);
+        assert_eq!(cursor.byte_pos, 6);
+        assert_eq!(cursor.chars.as_str(), "d");
+        assert_eq!((cursor.line(), cursor.column()), (2, 1));
+
+        cursor.seek_to(1);
+        assert_eq!(cursor.byte_pos, 1);
+        assert_eq!(cursor.chars.as_str(), "b\n竜d");
+        assert_eq!((cursor.line(), cursor.column()), (1, 1));
+
+        cursor.seek_to(input.len());
+        assert_eq!(cursor.chars.as_str(), "");
+    }
+
+    #[test]
+    #[should_panic]
+    fn seek_to_not_char_boundary() {
+        let mut cursor = Cursor::new("竜");
+        cursor.seek_to(1);
+    }
+
+    #[test]
+    fn iter() {
+        let input = "a竜\nb";
+        let mut cursor = Cursor::new(input).tracked();
+        assert_eq!(cursor.iter().size_hint(), (2, Some(6)));
+
+        let mut collected = ['\0'; 3];
+        for (slot, c) in collected.iter_mut().zip(cursor.iter()) {
+            *slot = c;
+        }
+        assert_eq!(collected, ['a', '竜', '\n']);
+        assert_eq!(cursor.byte_pos, 5);
+        assert_eq!((cursor.line(), cursor.column()), (2, 0));
+
+        let mut iter = cursor.iter();
+        assert_eq!(iter.next(), Some('b'));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.size_hint(), (0, Some(0)));
+        drop(iter);
+        assert_eq!(cursor.byte_pos, input.len());
+    }
+
+    #[test]
+    fn inherent_methods_through_mut_ref() {
+        // Closures receive `&mut Cursor`, through which the inherent methods must still be found
+        let tokens = Cursor::new("ab;c;").tracked().tokenize(|cursor| {
+            let end = cursor.find_byte_pos(';')?;
+            let start = cursor.position();
+            cursor.seek_to(end + 1);
+            Some((start, end))
+        });
+        assert!(tokens.eq([
+            (
+                Position {
+                    byte: 0,
+                    line: 1,
+                    column: 0
+                },
+                2
+            ),
+            (
+                Position {
+                    byte: 3,
+                    line: 1,
+                    column: 3
+                },
+                4
+            ),
+        ]));
+    }
+
+    #[test]
+    fn is_eof() {
+        let cursor = Cursor::new("");
+        assert!(cursor.is_eof());
+
+        let mut cursor = Cursor::new("竜");
+        assert!(!cursor.is_eof());
+        cursor.bump();
+        assert!(cursor.is_eof());
+    }
+
+    #[test]
+    fn bump_if() {
+        let input = "竜a";
+        let mut cursor = Cursor::new(input);
+        assert_eq!(cursor.bump_if(|c| c == 'a'), None);
+        assert_eq!(cursor.byte_pos, 0);
+        assert_eq!(cursor.chars.as_str(), input);
+
+        assert_eq!(cursor.bump_if(|c| c == '竜'), Some('竜'));
+        assert_eq!(cursor.byte_pos, 3);
+        assert_eq!(cursor.chars.as_str(), "a");
+
+        let mut cursor = Cursor::new("");
+        assert_eq!(cursor.bump_if(|_| true), None);
+        assert_eq!(cursor.byte_pos, 0);
+    }
+
+    #[test]
+    fn eat() {
+        let input = "竜=";
+        let mut cursor = Cursor::new(input);
+        assert!(!cursor.eat('='));
+        assert_eq!(cursor.byte_pos, 0);
+        assert_eq!(cursor.chars.as_str(), input);
+
+        assert!(cursor.eat('竜'));
+        assert_eq!(cursor.byte_pos, 3);
+        assert!(cursor.eat('='));
+        assert_eq!(cursor.byte_pos, 4);
+        assert!(!cursor.eat('='));
+    }
+
+    #[test]
+    fn eat_str() {
+        let input = "let竜\nx";
+        let mut cursor = Cursor::new(input).tracked();
+        assert!(!cursor.eat_str("lex"));
+        assert_eq!(cursor.byte_pos, 0);
+        assert_eq!(cursor.chars.as_str(), input);
+
+        assert!(cursor.eat_str("let"));
+        assert_eq!(cursor.byte_pos, 3);
+        assert_eq!(cursor.chars.as_str(), "竜\nx");
+
+        assert!(cursor.eat_str("竜\n"));
+        assert_eq!(cursor.byte_pos, 7);
+        assert_eq!((cursor.line(), cursor.column()), (2, 0));
+
+        assert!(cursor.eat_str(""));
+        assert!(!cursor.eat_str("xy"));
+        assert_eq!(cursor.chars.as_str(), "x");
+    }
+
+    #[test]
+    fn starts_with() {
+        let input = "竜王";
+        let cursor = Cursor::new(input);
+        assert!(cursor.starts_with("竜"));
+        assert!(cursor.starts_with("竜王"));
+        assert!(cursor.starts_with(""));
+        assert!(!cursor.starts_with("王"));
+        assert!(!cursor.starts_with("竜王竜"));
+        assert_eq!(cursor.byte_pos, 0);
+        assert_eq!(cursor.chars.as_str(), input);
+    }
+
+    #[test]
+    fn skip_whitespace() {
+        let input = " \t\n\u{3000}a";
+        let mut cursor = Cursor::new(input);
+        cursor.skip_whitespace();
+        assert_eq!(cursor.byte_pos, 6);
+        assert_eq!(cursor.chars.as_str(), "a");
+
+        let mut cursor = Cursor::new(input);
+        cursor.skip_ascii_whitespace();
+        assert_eq!(cursor.byte_pos, 3);
+        assert_eq!(cursor.chars.as_str(), "\u{3000}a");
+    }
+
+    #[test]
+    fn skip_until() {
+        let input = "// 竜\nnext";
+        let mut cursor = Cursor::new(input);
+        cursor.skip_until(|c| c == '\n');
+        assert_eq!(cursor.byte_pos, 6);
+        assert_eq!(cursor.chars.as_str(), "\nnext");
+
+        cursor.skip_until(|c| c == '\n');
+        assert_eq!(cursor.byte_pos, 6);
+
+        cursor.bump();
+        cursor.skip_until(|c| c == '\n');
+        assert_eq!(cursor.byte_pos, input.len());
+        assert_eq!(cursor.chars.as_str(), "");
+    }
+
+    #[test]
+    fn count_while() {
+        let input = "##竜竜 heading";
+        let mut cursor = Cursor::new(input);
+        assert_eq!(cursor.count_while(|c| c == '#'), 2);
+        assert_eq!(cursor.byte_pos, 2);
+
+        assert_eq!(cursor.count_while(|c| c == '竜'), 2);
+        assert_eq!(cursor.byte_pos, 8);
+        assert_eq!(cursor.chars.as_str(), " heading");
+
+        assert_eq!(cursor.count_while(|c| c == '#'), 0);
+        assert_eq!(cursor.byte_pos, 8);
+    }
+
+    #[test]
+    fn bump_n() {
+        let input = "ab竜cd";
+        let mut cursor = Cursor::new(input);
+        assert_eq!(cursor.bump_n(0), "");
+        assert_eq!(cursor.byte_pos, 0);
+
+        assert_eq!(cursor.bump_n(3), "ab竜");
+        assert_eq!(cursor.byte_pos, 5);
+        assert_eq!(cursor.chars.as_str(), "cd");
+
+        assert_eq!(cursor.bump_n(10), "cd");
+        assert_eq!(cursor.byte_pos, input.len());
+        assert_eq!(cursor.chars.as_str(), "");
+    }
+
+    #[test]
+    fn skip_while_byte() {
+        let input = "123\n45竜6";
+        let mut cursor = Cursor::new(input).tracked();
+        cursor.skip_while_byte(|b| b.is_ascii_digit());
+        assert_eq!(cursor.byte_pos, 3);
+        assert_eq!(cursor.chars.as_str(), "\n45竜6");
+
+        // Non-ASCII bytes always stop the skip
+        cursor.skip_while_byte(|_| true);
+        assert_eq!(cursor.byte_pos, 6);
+        assert_eq!(cursor.chars.as_str(), "竜6");
+        assert_eq!((cursor.line(), cursor.column()), (2, 2));
+        assert_eq!(cursor.char_pos(), 6);
+    }
+
+    #[test]
+    fn skip_while_byte_tracking() {
+        let input = "a\r\n\rb\n\tc\r\nd";
+        let mut cursor = Cursor::new(input).tracked();
+        cursor.skip_while_byte(|b| b != b'c');
+        assert_eq!((cursor.line(), cursor.column()), (4, 1));
+        assert_eq!(cursor.char_pos(), 7);
+
+        // A `\r\n` split across calls is still a single line break
+        cursor.skip_while_byte(|b| b != b'\n');
+        assert_eq!((cursor.line(), cursor.column()), (5, 0));
+        cursor.skip_while_byte(|_| true);
+        assert_eq!((cursor.line(), cursor.column()), (5, 1));
+        assert_eq!(cursor.byte_pos, input.len());
+
+        // Same result as bumping character by character
+        let mut bumped = Cursor::new(input).tracked();
+        bumped.skip_while(|_| true);
+        assert_eq!(bumped.position(), cursor.position());
+        assert_eq!(bumped.char_pos(), cursor.char_pos());
+
+        let mut cursor = Cursor::new("ab\tc").tracked();
+        cursor.set_tab_width(4);
+        cursor.skip_while_byte(|_| true);
+        assert_eq!((cursor.line(), cursor.column()), (1, 5));
+    }
+
+    #[test]
+    fn char_pos() {
+        let input = "a竜b竜竜c";
+        // Characters are counted without tracking lines and columns
+        let mut cursor = Cursor::new(input);
+        assert_eq!(cursor.char_pos(), 0);
+
+        cursor.bump_two();
+        assert_eq!(cursor.char_pos(), 2);
+        assert_eq!(cursor.byte_pos, 4);
+
+        cursor.bump();
+        cursor.skip_while(|c| c == '竜');
+        assert_eq!(cursor.char_pos(), 5);
+        assert_eq!(cursor.byte_pos, 11);
+
+        cursor.seek_to(1);
+        assert_eq!(cursor.char_pos(), 1);
+        cursor.seek_to(8);
+        assert_eq!(cursor.char_pos(), 4);
+        let checkpoint = cursor.checkpoint();
+
+        cursor.bump_back();
+        assert_eq!(cursor.char_pos(), 3);
+        cursor.restore(checkpoint);
+        assert_eq!(cursor.char_pos(), 4);
+        cursor.bump();
+        cursor.skip_while_byte(|_| true);
+        assert_eq!(cursor.char_pos(), 6);
+        assert_eq!(cursor.clone().tracked().char_pos(), 6);
+    }
+
+    #[test]
+    fn checkpoint() {
+        let input = "ab\n竜c";
+        let mut cursor = Cursor::new(input).tracked();
+        cursor.bump();
+        let checkpoint = cursor.checkpoint();
+
+        // A failed speculative match
+        assert!(!cursor.eat_str("b\n竜d"));
+        cursor.skip_while(|c| c != 'c');
+        assert_eq!((cursor.line(), cursor.column()), (2, 1));
+
+        cursor.restore(checkpoint);
+        assert_eq!(cursor.byte_pos, 1);
+        assert_eq!(cursor.char_pos(), 1);
+        assert_eq!((cursor.line(), cursor.column()), (1, 1));
+        assert_eq!(cursor.chars.as_str(), "b\n竜c");
+    }
+
+    #[test]
+    fn clone() {
+        let input = "ab\ncd";
+        let mut cursor = Cursor::new(input).tracked();
+        cursor.bump();
+        let save = cursor.clone();
+
+        cursor.skip_while(|_| true);
+        assert_eq!(save.byte_pos, 1);
+        assert_eq!(save.chars.as_str(), "b\ncd");
+
+        cursor = save;
+        assert_eq!(cursor.byte_pos, 1);
+        assert_eq!((cursor.line(), cursor.column()), (1, 1));
+        assert_eq!(cursor.chars.as_str(), "b\ncd");
+    }
+
+    #[test]
+    fn debug() {
+        let mut cursor = Cursor::new("a\"竜");
+        cursor.bump();
+        assert_eq!(
+            format!("{cursor:?}"),
+            r#"Cursor { byte_pos: 1, remaining: "\"竜" }"#
+        );
+
+        let cursor = Cursor::new("0123456789abcdefghijklmnopqrstuvwxyz");
+        assert_eq!(
+            format!("{cursor:?}"),
+            r#"Cursor { byte_pos: 0, remaining: "0123456789abcdefghijklmnopqrstuv"... }"#
+        );
+    }
+
+    #[test]
+    fn peek_while() {
+        let input = "竜竜a";
+        let cursor = Cursor::new(input);
+        assert_eq!(cursor.peek_while(|c| c == '竜'), "竜竜");
+        assert_eq!(cursor.peek_while(|c| c == 'a'), "");
+        assert_eq!(cursor.peek_while(|_| true), input);
+        assert_eq!(cursor.byte_pos, 0);
+        assert_eq!(cursor.chars.as_str(), input);
+    }
+
+    #[test]
+    fn expect() {
+        let input = "(竜";
+        let mut cursor = Cursor::new(input);
+        assert_eq!(cursor.expect('('), Ok(()));
+        assert_eq!(cursor.byte_pos, 1);
+
+        assert_eq!(
+            cursor.expect(')'),
+            Err(ExpectError {
+                expected: ')',
+                found: Some('竜'),
+                byte_pos: 1
+            })
+        );
+        assert_eq!(cursor.byte_pos, 1);
+        assert_eq!(cursor.chars.as_str(), "竜");
+
+        cursor.bump();
+        assert_eq!(
+            cursor.expect(')'),
+            Err(ExpectError {
+                expected: ')',
+                found: None,
+                byte_pos: 4
+            })
+        );
+    }
+
+    #[test]
+    fn with_offset() {
+        let document = "let x = 竜;";
+        let mut cursor = Cursor::with_offset(&document[8..], 8);
+        assert_eq!(cursor.byte_pos, 8);
+
+        cursor.bump();
+        assert_eq!(cursor.byte_pos, 11);
+        assert_eq!(&document[cursor.byte_pos..], ";");
+        assert_eq!(cursor.consumed(), "竜");
+
+        cursor.seek_to(8);
+        assert_eq!(cursor.chars.as_str(), "竜;");
+
+        cursor.bump();
+        cursor.reset();
+        assert_eq!(cursor.byte_pos, 8);
+        assert_eq!(cursor.chars.as_str(), "竜;");
+    }
+
+    #[test]
+    #[should_panic]
+    fn seek_to_before_offset() {
+        let mut cursor = Cursor::with_offset("a", 1);
+        cursor.seek_to(0);
+    }
+
+    #[test]
+    fn spanned() {
+        let input = "foo(竜)";
+        let mut cursor = Cursor::new(input);
+        let ((ident, (inner, inner_span)), span) = cursor.spanned(|cursor| {
+            let ident = cursor.bump_while(char::is_alphabetic);
+            cursor.bump();
+            let inner = cursor.spanned(|cursor| cursor.bump_while(|c| c != ')'));
+            cursor.bump();
+            (ident, inner)
+        });
+        assert_eq!(ident, "foo");
+        assert_eq!(inner, "竜");
+        assert_eq!(inner_span, 4..7);
+        assert_eq!(span, 0..input.len());
+
+        let ((), span) = cursor.spanned(|_| ());
+        assert_eq!(span, input.len()..input.len());
+    }
+
+    #[test]
+    fn span_from() {
+        let input = "ab竜";
+        let mut cursor = Cursor::new(input);
+        let start = cursor.byte_pos();
+        assert!(cursor.span_from(start).is_empty());
+
+        cursor.bump();
+        let start = cursor.byte_pos();
+        cursor.bump_two();
+        let span = cursor.span_from(start);
+        assert_eq!(span, Span { start: 1, end: 5 });
+        assert_eq!(span.len(), 4);
+        assert!(!span.is_empty());
+        assert_eq!(&input[Range::from(span)], "b竜");
+        assert_eq!(Span::from(1..5), span);
+    }
+
+    #[test]
+    fn prev() {
+        let input = "a竜";
+        let mut cursor = Cursor::new(input);
+        assert_eq!(cursor.prev(), None);
+
+        cursor.bump();
+        assert_eq!(cursor.prev(), Some('a'));
+
+        cursor.bump();
+        assert_eq!(cursor.prev(), Some('竜'));
+        assert_eq!(cursor.byte_pos, input.len());
+    }
+
+    #[test]
+    fn bump_back() {
+        let input = "a\n竜";
+        let mut cursor = Cursor::new(input).tracked();
+        cursor.skip_while(|_| true);
+        assert_eq!((cursor.line(), cursor.column()), (2, 1));
+
+        assert_eq!(cursor.bump_back(), Some('竜'));
+        assert_eq!(cursor.byte_pos, 2);
+        assert_eq!(cursor.char_pos(), 2);
+        assert_eq!((cursor.line(), cursor.column()), (2, 0));
+        assert_eq!(cursor.chars.as_str(), "竜");
+
+        assert_eq!(cursor.bump_back(), Some('\n'));
+        assert_eq!(cursor.byte_pos, 1);
+        assert_eq!((cursor.line(), cursor.column()), (1, 1));
+
+        assert_eq!(cursor.bump_back(), Some('a'));
+        assert_eq!(cursor.bump_back(), None);
+        assert_eq!(cursor.byte_pos, 0);
+        assert_eq!(cursor.chars.as_str(), input);
+    }
+
+    #[test]
+    fn bump_back_line_breaks_and_tabs() {
+        let input = "a\tb\r\n\t竜\r\rc\n\td";
+        let start = Position {
+            byte: 10,
+            line: 5,
+            column: 3,
+        };
+        let mut cursor = TrackedCursor::new_at(input, start);
+        cursor.set_tab_width(4);
+        let fresh = cursor.clone();
+        cursor.skip_while(|_| true);
+        // Same positions as when scanning the input from the start
+        while cursor.bump_back().is_some() {
+            assert_eq!(cursor.position(), fresh.position_at(cursor.byte_pos));
+        }
+        assert_eq!(cursor.position(), start);
+    }
+
+    #[test]
+    fn remaining_len() {
+        let input = "a竜b";
+        let mut cursor = Cursor::new(input);
+        assert_eq!(cursor.remaining_len(), 5);
+        assert_eq!(cursor.remaining_char_count(), 3);
+
+        cursor.bump();
+        assert_eq!(cursor.remaining_len(), 4);
+        assert_eq!(cursor.remaining_char_count(), 2);
+        assert_eq!(cursor.byte_pos, 1);
+    }
+
+    #[test]
+    fn from_bytes() {
+        let cursor = Cursor::from_bytes("a竜".as_bytes()).unwrap();
+        assert_eq!(cursor.byte_pos, 0);
+        assert_eq!(cursor.chars.as_str(), "a竜");
+
+        let error = Cursor::from_bytes(b"a\xE7\xAB").unwrap_err();
+        assert_eq!(error.valid_up_to(), 1);
+    }
+
+    #[test]
+    fn peek_is() {
+        let cursor = Cursor::new("竜1");
+        assert!(cursor.peek_is(char::is_alphabetic));
+        assert!(!cursor.peek_is(|c| c.is_ascii_digit()));
+        assert_eq!(cursor.byte_pos, 0);
+
+        let cursor = Cursor::new("");
+        assert!(!cursor.peek_is(|_| true));
+    }
+
+    #[test]
+    fn bump_until_str() {
+        let input = "竜 comment */ rest";
+        let mut cursor = Cursor::new(input);
+        assert_eq!(cursor.bump_until_str("*/"), "竜 comment ");
+        assert_eq!(cursor.byte_pos, 12);
+        assert_eq!(cursor.chars.as_str(), "*/ rest");
+
+        assert_eq!(cursor.bump_until_str("*/"), "");
+        assert_eq!(cursor.byte_pos, 12);
+
+        assert_eq!(cursor.bump_until_str("\n"), "*/ rest");
+        assert_eq!(cursor.byte_pos, input.len());
+        assert_eq!(cursor.chars.as_str(), "");
+    }
+
+    #[test]
+    fn eat_str_ignore_ascii_case() {
+        let input = "SeLeCt 竜Ä";
+        let mut cursor = Cursor::new(input);
+        assert!(!cursor.eat_str_ignore_ascii_case("selected"));
+        assert!(cursor.eat_str_ignore_ascii_case("select"));
+        assert_eq!(cursor.byte_pos, 6);
+        assert_eq!(cursor.chars.as_str(), " 竜Ä");
+
+        // Non-ASCII characters are compared exactly
+        assert!(!cursor.eat_str_ignore_ascii_case(" 竜ä"));
+        assert_eq!(cursor.byte_pos, 6);
+        assert!(cursor.eat_str_ignore_ascii_case(" 竜Ä"));
+        assert_eq!(cursor.byte_pos, input.len());
+    }
+
+    #[test]
+    fn bump_byte() {
+        let input = "a\n竜";
+        let mut cursor = Cursor::new(input).tracked();
+        assert_eq!(cursor.bump_byte(), Some(b'a'));
+        assert_eq!(cursor.bump_byte(), Some(b'\n'));
+        assert_eq!(cursor.byte_pos, 2);
+        assert_eq!((cursor.line(), cursor.column()), (2, 0));
+
+        // Multi-byte characters are never split
+        assert_eq!(cursor.bump_byte(), None);
+        assert_eq!(cursor.byte_pos, 2);
+        assert_eq!(cursor.chars.as_str(), "竜");
+
+        assert_eq!(cursor.bump(), Some('竜'));
+        assert_eq!(cursor.bump_byte(), None);
+    }
+
+    #[test]
+    fn bump_while_indexed() {
+        let input = "竜a1 2";
+        let mut cursor = Cursor::new(input);
+        let ident = cursor.bump_while_indexed(|i, c| {
+            if i == 0 {
+                c.is_alphabetic()
+            } else {
+                c.is_alphanumeric()
+            }
+        });
+        assert_eq!(ident, "竜a1");
+        assert_eq!(cursor.byte_pos, 5);
+        assert_eq!(cursor.chars.as_str(), " 2");
+
+        cursor.bump();
+        assert_eq!(
+            cursor.bump_while_indexed(|i, c| i > 0 || c.is_alphabetic()),
+            ""
+        );
+        assert_eq!(cursor.byte_pos, 6);
+    }
+
+    #[test]
+    fn find_byte_pos() {
+        let input = "a竜,b,";
+        let mut cursor = Cursor::new(input);
+        assert_eq!(cursor.find_byte_pos(','), Some(4));
+        assert_eq!(cursor.find_byte_pos('竜'), Some(1));
+        assert_eq!(cursor.find_byte_pos(';'), None);
+        assert_eq!(cursor.byte_pos, 0);
+
+        cursor.bump_n(3);
+        assert_eq!(cursor.find_byte_pos(','), Some(6));
+        assert_eq!(cursor.find_byte_pos('竜'), None);
+    }
+
+    #[test]
+    fn eq() {
+        let mut cursor = Cursor::new("ab");
+        let mut expected = Cursor::new("ab");
+        assert_eq!(cursor, expected);
+
+        cursor.bump();
+        assert_ne!(cursor, expected);
+        expected.bump();
+        assert_eq!(cursor, expected);
+
+        // Same position but different remaining input
+        let mut other = Cursor::new("ac");
+        other.bump();
+        assert_ne!(cursor, other);
+    }
+
+    #[test]
+    fn slice() {
+        let input = "a竜b";
+        let mut cursor = Cursor::new(input);
+        let start = cursor.byte_pos();
+        cursor.bump_two();
+        assert_eq!(cursor.slice(start), "a竜");
+        assert_eq!(cursor.slice(1), "竜");
+        assert_eq!(cursor.slice(cursor.byte_pos()), "");
+
+        let mut cursor = Cursor::with_offset("竜b", 10);
+        cursor.bump();
+        assert_eq!(cursor.slice(10), "竜");
+    }
+
+    #[test]
+    #[should_panic]
+    fn slice_not_char_boundary() {
+        let mut cursor = Cursor::new("竜");
+        cursor.bump();
+        cursor.slice(1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn slice_after_cursor() {
+        let cursor = Cursor::new("ab");
+        cursor.slice(1);
+    }
+
+    #[test]
+    fn line_breaks() {
+        for input in ["a\nb\n", "a\r\nb\r\n", "a\rb\r", "a\nb\r\n", "a\r\nb\r"] {
+            let mut cursor = Cursor::new(input).tracked();
+            cursor.bump_n(2);
+            assert_eq!((cursor.line(), cursor.column()), (2, 0), "{input:?}");
+            cursor.eat('\n');
+            assert_eq!((cursor.line(), cursor.column()), (2, 0), "{input:?}");
+            cursor.bump();
+            assert_eq!((cursor.line(), cursor.column()), (2, 1), "{input:?}");
+            cursor.skip_while(|_| true);
+            assert_eq!((cursor.line(), cursor.column()), (3, 0), "{input:?}");
+        }
+
+        // Two lone `\r` are two line breaks
+        let mut cursor = Cursor::new("\r\r").tracked();
+        cursor.skip_while(|_| true);
+        assert_eq!((cursor.line(), cursor.column()), (3, 0));
+
+        // Seeking between `\r` and `\n`
+        let mut cursor = Cursor::new("\r\na").tracked();
+        cursor.seek_to(1);
+        assert_eq!((cursor.line(), cursor.column()), (2, 0));
+        cursor.bump();
+        assert_eq!((cursor.line(), cursor.column()), (2, 0));
+    }
+
+    #[test]
+    fn tab_width() {
+        let input = "\ta \tb\n  \t";
+        let mut cursor = Cursor::new(input).tracked();
+        cursor.skip_while(|c| c != '\n');
+        assert_eq!(cursor.column(), 5);
+
+        let mut cursor = Cursor::new(input).tracked();
+        cursor.set_tab_width(8);
+        cursor.bump();
+        assert_eq!(cursor.column(), 8);
+        cursor.bump_two();
+        assert_eq!(cursor.column(), 10);
+        cursor.bump();
+        assert_eq!(cursor.column(), 16);
+        cursor.skip_while(|_| true);
+        assert_eq!((cursor.line(), cursor.column()), (2, 8));
+        assert_eq!(cursor.byte_pos, input.len());
+
+        // The width can be set again after going back to the start
+        cursor.reset();
+        cursor.set_tab_width(4);
+        cursor.bump();
+        assert_eq!(cursor.column(), 4);
+    }
+
+    #[test]
+    #[should_panic]
+    fn set_tab_width_after_consuming() {
+        let mut cursor = Cursor::new("\ta\tb").tracked();
+        cursor.bump();
+        cursor.set_tab_width(4);
+    }
+
+    #[test]
+    fn peek_str() {
+        let input = "<竜>=";
+        let cursor = Cursor::new(input);
+        assert_eq!(cursor.peek_str(0), "");
+        assert_eq!(cursor.peek_str(2), "<竜");
+        assert_eq!(cursor.peek_str(4), input);
+        assert_eq!(cursor.peek_str(10), input);
+        assert_eq!(cursor.byte_pos, 0);
+        assert_eq!(cursor.chars.as_str(), input);
+    }
+
+    #[test]
+    fn bump_array() {
+        let input = "a竜";
+        let mut cursor = Cursor::new(input);
+        assert_eq!(cursor.bump_array::<0>(), []);
+        assert_eq!(cursor.bump_array::<3>(), [Some('a'), Some('竜'), None]);
+        assert_eq!(cursor.byte_pos, 4);
+        assert_eq!(cursor.chars.as_str(), "");
+        assert_eq!(cursor.bump_array::<2>(), [None, None]);
+        assert_eq!(cursor.byte_pos, 4);
+    }
+
+    #[test]
+    fn peek_array() {
+        let input = "<=>";
+        let cursor = Cursor::new(input);
+        assert_eq!(cursor.peek_array(), [Some('<'), Some('='), Some('>')]);
+        assert_eq!(cursor.peek_array::<2>(), [Some('<'), Some('=')]);
+        assert_eq!(cursor.peek_array::<4>()[3], None);
         assert_eq!(cursor.byte_pos, 0);
         assert_eq!(cursor.chars.as_str(), input);
+    }
+
+    #[test]
+    fn try_seek_to() {
+        let input = "a竜";
+        let mut cursor = Cursor::new(input);
+        assert_eq!(cursor.try_seek_to(1), Ok(()));
+        assert_eq!(cursor.byte_pos, 1);
+
+        assert_eq!(cursor.try_seek_to(2), Err(NotCharBoundary { byte_pos: 2 }));
+        assert_eq!(cursor.try_seek_to(5), Err(NotCharBoundary { byte_pos: 5 }));
+        assert_eq!(cursor.byte_pos, 1);
+        assert_eq!(cursor.chars.as_str(), "竜");
+    }
+
+    #[test]
+    fn checked_slice() {
+        let mut cursor = Cursor::new("a竜b");
+        cursor.bump_two();
+        assert_eq!(cursor.checked_slice(1), Some("竜"));
+        assert_eq!(cursor.checked_slice(2), None);
+        assert_eq!(cursor.checked_slice(5), None);
+    }
+
+    #[test]
+    fn eat_longest() {
+        let operators = [">", ">=", ">>", ">>="];
+        let input = ">>=>竜";
+        let mut cursor = Cursor::new(input);
+        assert_eq!(cursor.eat_longest(&operators), Some(">>="));
+        assert_eq!(cursor.byte_pos, 3);
+        assert_eq!(cursor.eat_longest(&operators), Some(">"));
+        assert_eq!(cursor.byte_pos, 4);
+
+        assert_eq!(cursor.eat_longest(&operators), None);
+        assert_eq!(cursor.eat_longest(&[]), None);
+        assert_eq!(cursor.byte_pos, 4);
+        assert_eq!(cursor.chars.as_str(), "竜");
+    }
+
+    #[test]
+    fn skip_line() {
+        let input = "// a\nb\r\nc\rd";
+        let mut cursor = Cursor::new(input).tracked();
+        cursor.skip_line();
+        assert_eq!(cursor.chars.as_str(), "b\r\nc\rd");
+        cursor.skip_line();
+        assert_eq!(cursor.chars.as_str(), "c\rd");
+        cursor.skip_line();
+        assert_eq!(cursor.chars.as_str(), "d");
+        assert_eq!((cursor.line(), cursor.column()), (4, 0));
+
+        // No trailing line break
+        cursor.skip_line();
+        assert_eq!(cursor.byte_pos, input.len());
+        assert_eq!(cursor.chars.as_str(), "");
+        cursor.skip_line();
+        assert_eq!(cursor.byte_pos, input.len());
+    }
+
+    #[test]
+    fn remaining_lines() {
+        let input = "a\r\n竜b\n\nc\rd\n";
+        let mut cursor = Cursor::new(input);
+        cursor.bump();
+        let mut lines = cursor.remaining_lines();
+        assert_eq!(lines.next(), Some((Span { start: 1, end: 1 }, "")));
+        assert_eq!(lines.next(), Some((Span { start: 3, end: 7 }, "竜b")));
+        assert_eq!(lines.next(), Some((Span { start: 8, end: 8 }, "")));
+        assert_eq!(lines.next(), Some((Span { start: 9, end: 10 }, "c")));
+        assert_eq!(lines.next(), Some((Span { start: 11, end: 12 }, "d")));
+        assert_eq!(lines.next(), None);
+        assert_eq!(cursor.byte_pos, 1);
+
+        let cursor = Cursor::new("a\nb");
+        let mut lines = cursor.remaining_lines();
+        assert_eq!(lines.next(), Some((Span { start: 0, end: 1 }, "a")));
+        assert_eq!(lines.next(), Some((Span { start: 2, end: 3 }, "b")));
+        assert_eq!(lines.next(), None);
+
+        assert_eq!(Cursor::new("").remaining_lines().next(), None);
+    }
+
+    #[test]
+    fn bump_map() {
+        let mut cursor = Cursor::new("竜");
+        assert_eq!(cursor.bump_map(char::len_utf8), Some(3));
+        assert_eq!(cursor.byte_pos, 3);
+        assert_eq!(cursor.bump_map(char::len_utf8), None);
+    }
+
+    #[test]
+    fn starts_with_any() {
+        let input = "竜=>";
+        let cursor = Cursor::new(input);
+        assert_eq!(cursor.starts_with_any(&["=", "竜", "竜="]), Some(1));
+        assert_eq!(cursor.starts_with_any(&["竜=", "竜"]), Some(0));
+        assert_eq!(cursor.starts_with_any(&["=>"]), None);
+        assert_eq!(cursor.starts_with_any(&[]), None);
+        assert_eq!(cursor.byte_pos, 0);
+    }
+
+    #[test]
+    fn skip_bom() {
+        let mut cursor = Cursor::new("\u{FEFF}a");
+        cursor.skip_bom();
+        assert_eq!(cursor.byte_pos, 3);
+        assert_eq!(cursor.chars.as_str(), "a");
+
+        let mut cursor = Cursor::new("a");
+        cursor.skip_bom();
+        assert_eq!(cursor.byte_pos, 0);
+        assert_eq!(cursor.chars.as_str(), "a");
+
+        // Only at the start of the input
+        let mut cursor = Cursor::new("a\u{FEFF}");
+        cursor.bump();
+        cursor.skip_bom();
+        assert_eq!(cursor.byte_pos, 1);
+        assert_eq!(cursor.chars.as_str(), "\u{FEFF}");
+
+        // A fragment in the middle of a document doesn't start with a BOM
+        let mut cursor = Cursor::with_offset("\u{FEFF}a", 5);
+        cursor.skip_bom();
+        assert_eq!(cursor.byte_pos, 5);
+        assert_eq!(cursor.chars.as_str(), "\u{FEFF}a");
+    }
+
+    #[test]
+    fn bump_while_counted() {
+        let input = "12竜3x";
+        let mut cursor = Cursor::new(input);
+        assert_eq!(cursor.bump_while_counted(|c| c != 'x'), ("12竜3", 4));
+        assert_eq!(cursor.byte_pos, 6);
+        assert_eq!(cursor.bump_while_counted(|c| c.is_ascii_digit()), ("", 0));
+        assert_eq!(cursor.chars.as_str(), "x");
+    }
+
+    #[test]
+    fn display_errors() {
+        let error = ExpectError {
+            expected: ')',
+            found: Some('竜'),
+            byte_pos: 3,
+        };
+        assert_eq!(
+            format!("{error}"),
+            "expected ')', found '竜' at byte position 3"
+        );
+
+        let error = ExpectError {
+            expected: '\n',
+            found: None,
+            byte_pos: 0,
+        };
+        assert_eq!(
+            format!("{error}"),
+            "expected '\\n', found end of input at byte position 0"
+        );
+
+        let error = NotCharBoundary { byte_pos: 1 };
+        assert_eq!(
+            format!("{error}"),
+            "byte position 1 is not a char boundary of the input"
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn std_errors() {
+        use std::boxed::Box;
+        use std::error::Error;
+
+        let errors: [Box<dyn Error>; 2] = [
+            Box::new(Cursor::new("").expect('a').unwrap_err()),
+            Box::new(Cursor::new("").try_seek_to(1).unwrap_err()),
+        ];
+        assert_eq!(errors.len(), 2);
+    }
 
-        let input = "a";
-        let cursor = Cursor::new(input);
-        assert_eq!(cursor.peek_two(), (Some('a'), None));
+    #[test]
+    fn split() {
+        let input = "ab竜";
+        let mut cursor = Cursor::new(input);
+        assert_eq!(cursor.split(), ("", input));
+        cursor.bump_two();
+        assert_eq!(cursor.split(), ("ab", "竜"));
+        cursor.bump();
+        assert_eq!(cursor.split(), (input, ""));
+    }
+
+    #[test]
+    fn seek_to_nearest_boundary() {
+        let input = "a竜b";
+        let mut cursor = Cursor::new(input);
+        cursor.seek_to_nearest_boundary(3);
+        assert_eq!(cursor.byte_pos, 1);
+        assert_eq!(cursor.chars.as_str(), "竜b");
+
+        cursor.seek_to_nearest_boundary(4);
+        assert_eq!(cursor.byte_pos, 4);
+
+        cursor.seek_to_nearest_boundary(100);
+        assert_eq!(cursor.byte_pos, input.len());
+
+        let mut cursor = Cursor::with_offset("竜", 10);
+        cursor.seek_to_nearest_boundary(5);
+        assert_eq!(cursor.byte_pos, 10);
+        cursor.seek_to_nearest_boundary(12);
+        assert_eq!(cursor.byte_pos, 10);
+    }
+
+    #[test]
+    fn bump_while_cf() {
+        let input = "ab竜\"c";
+        let mut cursor = Cursor::new(input);
+        let (consumed, reason) = cursor.bump_while_cf(|c| match c {
+            '"' => ControlFlow::Break("end"),
+            c if c.is_ascii() => ControlFlow::Continue(()),
+            _ => ControlFlow::Break("invalid"),
+        });
+        assert_eq!((consumed, reason), ("ab", Some("invalid")));
+        assert_eq!(cursor.byte_pos, 2);
+        assert_eq!(cursor.chars.as_str(), "竜\"c");
+
+        let (consumed, reason) = cursor.bump_while_cf(|_| ControlFlow::<()>::Continue(()));
+        assert_eq!((consumed, reason), ("竜\"c", None));
+        assert_eq!(cursor.byte_pos, input.len());
+    }
+
+    #[test]
+    fn new_at() {
+        let start = Position {
+            byte: 20,
+            line: 3,
+            column: 4,
+        };
+        let mut cursor = Cursor::new_at("a\nb", start);
+        assert_eq!(cursor.position(), start);
+
+        cursor.bump();
+        assert_eq!(
+            cursor.position(),
+            Position {
+                byte: 21,
+                line: 3,
+                column: 5
+            }
+        );
+        cursor.bump_two();
+        assert_eq!(
+            cursor.position(),
+            Position {
+                byte: 23,
+                line: 4,
+                column: 1
+            }
+        );
+
+        cursor.seek_to(21);
+        assert_eq!((cursor.line(), cursor.column()), (3, 5));
+        cursor.reset();
+        assert_eq!(cursor.position(), start);
+    }
+
+    #[test]
+    fn tokenize() {
+        let mut tokens = Cursor::new("12 竜 3").tokenize(|cursor| {
+            cursor.skip_whitespace();
+            let start = cursor.byte_pos();
+            cursor.bump_while(|c| !c.is_whitespace());
+            let span = cursor.span_from(start);
+            (!span.is_empty()).then_some(span)
+        });
+        assert_eq!(tokens.next(), Some(Span { start: 0, end: 2 }));
+        assert_eq!(tokens.next(), Some(Span { start: 3, end: 6 }));
+        assert_eq!(tokens.next(), Some(Span { start: 7, end: 8 }));
+        assert_eq!(tokens.next(), None);
+    }
+
+    #[test]
+    fn peek_nth_byte_pos() {
+        let input = "a竜b";
+        let mut cursor = Cursor::new(input);
+        assert_eq!(cursor.peek_nth_byte_pos(0), Some(0));
+        assert_eq!(cursor.peek_nth_byte_pos(1), Some(1));
+        assert_eq!(cursor.peek_nth_byte_pos(2), Some(4));
+        assert_eq!(cursor.peek_nth_byte_pos(3), Some(5));
+        assert_eq!(cursor.peek_nth_byte_pos(4), None);
         assert_eq!(cursor.byte_pos, 0);
-        assert_eq!(cursor.chars.as_str(), input);
 
-        let input = "";
-        let cursor = Cursor::new(input);
-        assert_eq!(cursor.peek_two(), (None, None));
+        cursor.bump();
+        assert_eq!(cursor.peek_nth_byte_pos(1), Some(4));
+    }
+
+    #[test]
+    fn skip_whitespace_spanned() {
+        let input = "a \n\tb";
+        let mut cursor = Cursor::new(input);
+        assert_eq!(cursor.skip_whitespace_spanned(), Span { start: 0, end: 0 });
         assert_eq!(cursor.byte_pos, 0);
-        assert_eq!(cursor.chars.as_str(), input);
+
+        cursor.bump();
+        assert_eq!(cursor.skip_whitespace_spanned(), Span { start: 1, end: 4 });
+        assert_eq!(cursor.chars.as_str(), "b");
     }
 
     #[test]
-    fn bump() {
-        let input = "a";
+    fn bump_escaped() {
+        let input = "a\\竜\\";
         let mut cursor = Cursor::new(input);
-        assert_eq!(cursor.bump(), Some('a'));
+        assert_eq!(cursor.bump_escaped('\\'), Some(('a', None)));
+        assert_eq!(cursor.bump_escaped('\\'), Some(('\\', Some('竜'))));
+        assert_eq!(cursor.byte_pos, 5);
+        assert_eq!(cursor.bump_escaped('\\'), Some(('\\', None)));
+        assert_eq!(cursor.byte_pos, input.len());
+        assert_eq!(cursor.bump_escaped('\\'), None);
+    }
+
+    #[test]
+    fn total_lines() {
+        assert_eq!(Cursor::new("").total_lines(), 1);
+        assert_eq!(Cursor::new("a").total_lines(), 1);
+
+        let input = "a\nb\r\nc\rd\n";
+        let mut cursor = Cursor::new(input).tracked();
+        assert_eq!(cursor.total_lines(), 5);
+        cursor.bump();
+        assert_eq!(cursor.total_lines(), 5);
+        cursor.skip_while(|_| true);
+        assert_eq!(cursor.line(), cursor.total_lines());
+
+        // Lines of the input itself, not of the document it's a fragment of
+        let start = Position {
+            byte: 10,
+            line: 5,
+            column: 2,
+        };
+        let mut cursor = TrackedCursor::new_at("a\nb", start);
+        assert_eq!(cursor.total_lines(), 2);
+        cursor.skip_while(|_| true);
+        assert_eq!(cursor.line(), 6);
+    }
+
+    #[test]
+    fn position_at() {
+        let input = "ab\n竜c\r\nd";
+        let mut cursor = Cursor::new(input).tracked();
+        cursor.bump();
+        assert_eq!(
+            cursor.position_at(6),
+            Position {
+                byte: 6,
+                line: 2,
+                column: 1
+            }
+        );
+        assert_eq!(
+            cursor.position_at(0),
+            Position {
+                byte: 0,
+                line: 1,
+                column: 0
+            }
+        );
+        assert_eq!(
+            cursor.position_at(input.len()),
+            Position {
+                byte: input.len(),
+                line: 3,
+                column: 1
+            }
+        );
         assert_eq!(cursor.byte_pos, 1);
-        assert_eq!(cursor.chars.as_str(), "");
     }
 
     #[test]
-    fn bump_two() {
-        let input = "abc";
+    #[should_panic]
+    fn position_at_not_char_boundary() {
+        Cursor::new("竜").tracked().position_at(2);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn line_index() {
+        use std::string::String;
+
+        let mut input = String::new();
+        for i in 0..3000 {
+            let line = match i % 4 {
+                0 => "fn 竜() {}\n",
+                1 => "\tlet x = 1;\r\n",
+                2 => "\r",
+                _ => "  // \t comment\n",
+            };
+            input.push_str(line);
+        }
+
+        let mut cursor = Cursor::new_at(
+            &input,
+            Position {
+                byte: 7,
+                line: 2,
+                column: 3,
+            },
+        );
+        cursor.set_tab_width(4);
+        let index = cursor.build_line_index();
+        loop {
+            assert_eq!(index.position(cursor.byte_pos), cursor.position());
+            if cursor.bump().is_none() {
+                break;
+            }
+        }
+        assert_eq!(cursor.line(), 3002);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    #[should_panic]
+    fn line_index_not_char_boundary() {
+        Cursor::new("竜").build_line_index().position(1);
+    }
+
+    #[test]
+    fn bump_spanned() {
+        let mut cursor = Cursor::new("a竜");
+        assert_eq!(
+            cursor.bump_spanned(),
+            Some(('a', Span { start: 0, end: 1 }))
+        );
+        assert_eq!(
+            cursor.bump_spanned(),
+            Some(('竜', Span { start: 1, end: 4 }))
+        );
+        assert_eq!(cursor.bump_spanned(), None);
+        assert_eq!(cursor.byte_pos, 4);
+    }
+
+    #[test]
+    fn is_remaining_ascii() {
+        let input = "ab竜c";
         let mut cursor = Cursor::new(input);
-        assert_eq!(cursor.bump_two(), (Some('a'), Some('b')));
+        assert!(!cursor.is_remaining_ascii());
+        assert_eq!(cursor.find_non_ascii(), Some(2));
+
+        cursor.bump_n(3);
+        assert!(cursor.is_remaining_ascii());
+        assert_eq!(cursor.find_non_ascii(), None);
+
+        let cursor = Cursor::new("");
+        assert!(cursor.is_remaining_ascii());
+        assert_eq!(cursor.find_non_ascii(), None);
+    }
+
+    #[test]
+    fn skip_while_nonempty() {
+        let input = "12a";
+        let mut cursor = Cursor::new(input);
+        assert!(!cursor.skip_while_nonempty(char::is_alphabetic));
+        assert_eq!(cursor.byte_pos, 0);
+        assert_eq!(cursor.chars.as_str(), input);
+
+        assert!(cursor.skip_while_nonempty(|c| c.is_ascii_digit()));
         assert_eq!(cursor.byte_pos, 2);
-        assert_eq!(cursor.chars.as_str(), "c");
+        assert_eq!(cursor.chars.as_str(), "a");
+    }
 
-        assert_eq!(cursor.bump_two(), (Some('c'), None));
-        assert_eq!(cursor.byte_pos, 3);
-        assert_eq!(cursor.chars.as_str(), "");
+    #[test]
+    fn bump_digit() {
+        let input = "7fG";
+        let mut cursor = Cursor::new(input);
+        assert_eq!(cursor.bump_digit(10), Some(7));
+        assert_eq!(cursor.bump_digit(10), None);
+        assert_eq!(cursor.byte_pos, 1);
+        assert_eq!(cursor.bump_digit(16), Some(15));
+        assert_eq!(cursor.bump_digit(16), None);
+        assert_eq!(cursor.bump_digit(36), Some(16));
+        assert_eq!(cursor.bump_digit(36), None);
+        assert_eq!(cursor.byte_pos, input.len());
+    }
 
-        assert_eq!(cursor.bump_two(), (None, None));
-        assert_eq!(cursor.byte_pos, 3);
-        assert_eq!(cursor.chars.as_str(), "");
+    #[test]
+    #[should_panic]
+    fn bump_digit_invalid_radix() {
+        Cursor::new("1").bump_digit(37);
     }
 
     #[test]
-    fn skip_while() {
-        let input = "aaaab";
+    #[should_panic]
+    fn bump_digit_invalid_radix_at_eof() {
+        Cursor::new("").bump_digit(1);
+    }
+
+    #[test]
+    fn bump_integer() {
+        let input = "00123+ff 18446744073709551616x";
         let mut cursor = Cursor::new(input);
-        cursor.skip_while(|c| c == 'a');
+        assert_eq!(cursor.bump_integer(10), Some((Some(123), "00123")));
+        assert_eq!(cursor.bump_integer(10), None);
+        assert_eq!(cursor.byte_pos, 5);
+
+        cursor.bump();
+        assert_eq!(cursor.bump_integer(16), Some((Some(255), "ff")));
+
+        // Overflowing digits are still consumed
+        cursor.bump();
+        assert_eq!(
+            cursor.bump_integer(10),
+            Some((None, "18446744073709551616"))
+        );
+        assert_eq!(cursor.chars.as_str(), "x");
+
+        let mut cursor = Cursor::new("18446744073709551615");
+        assert_eq!(cursor.bump_integer(10).unwrap().0, Some(u64::MAX));
+    }
+
+    #[test]
+    #[should_panic]
+    fn bump_integer_invalid_radix() {
+        Cursor::new("1").bump_integer(37);
+    }
+
+    #[test]
+    #[should_panic]
+    fn bump_integer_invalid_radix_at_eof() {
+        Cursor::new("").bump_integer(1);
+    }
+
+    #[test]
+    fn reinit() {
+        let mut cursor = Cursor::with_offset("a\n\tb", 10).tracked();
+        cursor.set_tab_width(4);
+        cursor.skip_while(|_| true);
+
+        cursor.reinit("\tc");
+        assert_eq!(cursor, Cursor::new("\tc").tracked());
+        assert_eq!(cursor.position(), Cursor::new("").tracked().position());
+        assert_eq!(cursor.char_pos(), 0);
+        assert_eq!(cursor.consumed(), "");
+
+        cursor.bump();
+        assert_eq!(cursor.column(), 4);
+        cursor.reset();
+        assert_eq!(cursor.byte_pos, 0);
+    }
+
+    #[test]
+    fn peek_while_count() {
+        let input = "1f竜x";
+        let cursor = Cursor::new(input);
+        assert_eq!(cursor.peek_while_count(|c| c.is_ascii_hexdigit()), 2);
+        assert_eq!(cursor.peek_while_count(|c| c != 'x'), 3);
+        assert_eq!(cursor.peek_while_count(|c| c == 'x'), 0);
+        assert_eq!(cursor.byte_pos, 0);
+        assert_eq!(cursor.chars.as_str(), input);
+    }
+
+    #[test]
+    fn peek_in_range() {
+        let cursor = Cursor::new("7a");
+        assert!(cursor.peek_in_range('0'..='9'));
+        assert!(!cursor.peek_in_range('a'..='z'));
+        assert!(!Cursor::new("").peek_in_range('\0'..=char::MAX));
+    }
+
+    #[test]
+    fn eat_in_range() {
+        let mut cursor = Cursor::new("42竜x");
+        assert_eq!(cursor.eat_in_range('0'..='9'), Some('4'));
+        assert_eq!(cursor.eat_in_range('0'..='9'), Some('2'));
+        assert_eq!(cursor.eat_in_range('0'..='9'), None);
+        assert_eq!(cursor.byte_pos, 2);
+        assert_eq!(cursor.eat_in_range('\u{4E00}'..='\u{9FFF}'), Some('竜'));
+        assert_eq!(cursor.eat_in_range('\u{4E00}'..='\u{9FFF}'), None);
+        assert_eq!(cursor.remaining(), "x");
+    }
+
+    #[test]
+    fn bytes() {
+        let mut cursor = Cursor::new("é!");
+        assert_eq!(cursor.bytes(), "é!".as_bytes());
+        cursor.bump();
+        assert_eq!(cursor.bytes(), b"!");
+        cursor.bump();
+        assert_eq!(cursor.bytes(), b"");
+    }
+
+    #[test]
+    fn consume_exact() {
+        let mut cursor = Cursor::new("let 竜x");
+        assert_eq!(cursor.consume_exact("let "), Ok(()));
+        assert_eq!(cursor.byte_pos, 4);
+
+        let error = cursor.consume_exact("竜y").unwrap_err();
+        assert_eq!(
+            error,
+            UnexpectedSequence {
+                expected: "竜y",
+                found: "竜x",
+                byte_pos: 4
+            }
+        );
+        assert_eq!(
+            format!("{error}"),
+            "expected \"竜y\", found \"竜x\" at byte position 4"
+        );
         assert_eq!(cursor.byte_pos, 4);
+        assert_eq!(cursor.chars.as_str(), "竜x");
+
+        cursor.bump_n(2);
+        let error = cursor.consume_exact(";").unwrap_err();
+        assert_eq!(error.found, "");
+        assert_eq!(
+            format!("{error}"),
+            "expected \";\", found end of input at byte position 8"
+        );
+    }
+
+    #[test]
+    fn bump_ident() {
+        let is_head = |c: char| c.is_alphabetic() || c == '_';
+        let is_tail = |c: char| c.is_alphanumeric() || c == '_';
+
+        let mut cursor = Cursor::new("_竜1 x");
+        assert_eq!(cursor.bump_ident(is_head, is_tail), Some("_竜1"));
+        assert_eq!(cursor.byte_pos, 5);
+        assert_eq!(cursor.bump_ident(is_head, is_tail), None);
+        assert_eq!(cursor.byte_pos, 5);
+        cursor.bump();
+        assert_eq!(cursor.bump_ident(is_head, is_tail), Some("x"));
+
+        let mut cursor = Cursor::new("1a");
+        assert_eq!(cursor.bump_ident(is_head, is_tail), None);
+        assert_eq!(cursor.chars.as_str(), "1a");
+    }
+
+    #[test]
+    fn char_indices() {
+        let document = "x = 竜!";
+        let mut cursor = Cursor::with_offset(&document[2..], 2);
+        cursor.bump_n(2);
+        let indices = cursor.char_indices();
+        // The cursor isn't borrowed by the iterator
+        cursor.bump();
+        assert!(indices.eq([(4, '竜'), (7, '!')]));
+        assert_eq!(cursor.byte_pos, 7);
+        assert!(cursor
+            .char_indices()
+            .all(|(i, c)| document[i..].starts_with(c)));
+    }
+
+    #[test]
+    fn limited() {
+        let input = "ab竜cd";
+        let mut cursor = Cursor::limited(input, 5).unwrap();
+        assert_eq!(cursor.bump_while(|_| true), "ab竜");
+        assert_eq!(cursor.peek(), None);
+        assert_eq!(cursor.bump(), None);
+        assert!(cursor.is_eof());
+        assert_eq!(&input[cursor.byte_pos..], "cd");
+
+        assert!(Cursor::limited(input, 0).unwrap().is_eof());
+        assert_eq!(
+            Cursor::limited(input, 3).unwrap_err(),
+            NotCharBoundary { byte_pos: 3 }
+        );
+        assert_eq!(
+            Cursor::limited(input, 8).unwrap_err(),
+            NotCharBoundary { byte_pos: 8 }
+        );
+    }
+
+    #[test]
+    fn partial_ord() {
+        let input = "abc";
+        let start = Cursor::new(input);
+        let mut end = start.clone();
+        end.bump_n(2);
+        assert!(start < end);
+        assert!(end > start);
+        assert!(start <= start.clone());
+
+        let mut cursors = [end.clone(), start.clone()];
+        cursors.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(cursors, [start.clone(), end]);
+
+        // Same byte position over different inputs
+        assert_eq!(start.partial_cmp(&Cursor::new("xyz")), None);
+    }
+
+    #[test]
+    fn expect_eof() {
+        let mut cursor = Cursor::new("1 竜");
+        cursor.bump();
+        let error = cursor.expect_eof().unwrap_err();
+        assert_eq!(
+            error,
+            TrailingInput {
+                remaining: " 竜",
+                byte_pos: 1
+            }
+        );
+        assert_eq!(
+            format!("{error}"),
+            "expected end of input, found \" 竜\" at byte position 1"
+        );
+        assert_eq!(cursor.byte_pos, 1);
+
+        cursor.bump_n(2);
+        assert_eq!(cursor.expect_eof(), Ok(()));
+        assert_eq!(Cursor::new("").expect_eof(), Ok(()));
+    }
+
+    #[test]
+    fn consume_bytes() {
+        let mut cursor = Cursor::new("a\n竜b").tracked();
+        cursor.consume_bytes(5);
+        assert_eq!(cursor.byte_pos, 5);
+        assert_eq!(cursor.char_pos(), 3);
+        assert_eq!(cursor.line(), 2);
+        assert_eq!(cursor.column(), 1);
         assert_eq!(cursor.chars.as_str(), "b");
+        cursor.consume_bytes(0);
+        assert_eq!(cursor.byte_pos, 5);
+        cursor.consume_bytes(1);
+        assert!(cursor.is_eof());
+    }
+
+    #[test]
+    #[should_panic]
+    fn consume_bytes_not_char_boundary() {
+        Cursor::new("竜").consume_bytes(1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn consume_bytes_out_of_bounds() {
+        Cursor::new("ab").consume_bytes(3);
+    }
+
+    #[test]
+    fn rewind_to() {
+        let mut cursor = Cursor::with_offset("a\n竜b\nc", 1).tracked();
+        cursor.bump_n(2);
+        let saved = cursor.position();
+        let expected = cursor.clone();
+
+        cursor.bump_n(3);
+        cursor.rewind_to(saved);
+        assert_eq!(cursor.position(), saved);
+        assert_eq!(cursor.char_pos(), expected.char_pos());
+        assert_eq!(cursor, expected);
+
+        let mut end = expected.clone();
+        end.bump_n(4);
+        let mut cursor = expected.clone();
+        cursor.rewind_to(end.position());
+        assert_eq!(cursor.position(), end.position());
+        assert_eq!(cursor.char_pos(), end.char_pos());
+        assert_eq!(cursor, end);
+    }
+
+    #[test]
+    #[should_panic]
+    fn rewind_to_not_char_boundary() {
+        let mut cursor = Cursor::new("竜").tracked();
+        let position = Position {
+            byte: 1,
+            ..cursor.position()
+        };
+        cursor.rewind_to(position);
+    }
+
+    #[test]
+    fn fold_while() {
+        let mut cursor = Cursor::new("ab竜!");
+        let count = cursor.fold_while(0, |count, c| {
+            if c.is_alphabetic() {
+                ControlFlow::Continue(count + 1)
+            } else {
+                ControlFlow::Break(count)
+            }
+        });
+        assert_eq!(count, 3);
+        assert_eq!(cursor.byte_pos, 5);
+        assert_eq!(cursor.chars.as_str(), "!");
+
+        let bytes = cursor.fold_while(0, |bytes, c| ControlFlow::Continue(bytes + c.len_utf8()));
+        assert_eq!(bytes, 1);
+        assert!(cursor.is_eof());
+        assert_eq!(cursor.fold_while(7, |_, _| ControlFlow::Break(0)), 7);
+    }
+
+    #[test]
+    fn words() {
+        let words = Cursor::new("  a  bb \t竜\n").words();
+        assert!(words.eq([
+            (Span { start: 2, end: 3 }, "a"),
+            (Span { start: 5, end: 7 }, "bb"),
+            (Span { start: 9, end: 12 }, "竜"),
+        ]));
+        assert_eq!(Cursor::new(" \n ").words().count(), 0);
+        assert_eq!(Cursor::new("").words().count(), 0);
+    }
+
+    #[test]
+    fn peek_pair() {
+        let mut cursor = Cursor::new("a竜");
+        assert_eq!(cursor.peek_pair(), (None, Some('a')));
+        cursor.bump();
+        assert_eq!(cursor.peek_pair(), (Some('a'), Some('竜')));
+        cursor.bump();
+        assert_eq!(cursor.peek_pair(), (Some('竜'), None));
+        assert_eq!(Cursor::new("").peek_pair(), (None, None));
+    }
+
+    #[test]
+    fn run() {
+        let mut cursor = Cursor::new("42\n竜").tracked();
+        let value = cursor.run(|s| {
+            let len = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+            (s[..len].parse::<u32>().unwrap(), len)
+        });
+        assert_eq!(value, 42);
+        assert_eq!(cursor.byte_pos, 2);
+        assert_eq!(cursor.run(|s| (s.len(), s.len())), 4);
+        assert_eq!(cursor.line(), 2);
+        assert_eq!(cursor.column(), 1);
+        assert!(cursor.is_eof());
+    }
+
+    #[test]
+    #[should_panic]
+    fn run_not_char_boundary() {
+        Cursor::new("竜").run(|_| ((), 2));
+    }
+
+    #[test]
+    fn skip_while_max() {
+        let mut cursor = Cursor::new("1f竜aB7}").tracked();
+        assert_eq!(cursor.skip_while_max(3, |c| c != '}'), 3);
+        assert_eq!(cursor.byte_pos, 5);
+        assert_eq!(cursor.char_pos(), 3);
+        assert_eq!(cursor.skip_while_max(6, |c| c.is_ascii_hexdigit()), 3);
+        assert_eq!(cursor.chars.as_str(), "}");
+        assert_eq!(cursor.skip_while_max(0, |_| true), 0);
+        assert_eq!(cursor.byte_pos, 8);
+    }
+
+    #[test]
+    fn subcursor() {
+        let document = "f(竜, x)";
+        let mut cursor = Cursor::with_offset(&document[1..], 1);
+        cursor.bump_n(4);
+        let mut inner = cursor.subcursor(Span { start: 2, end: 8 });
+        assert_eq!(inner.byte_pos, 2);
+        assert_eq!(inner.bump_while(|c| c != ','), "竜");
+        assert_eq!(&document[inner.byte_pos..], ", x)");
+        assert_eq!(inner.bump_while(|_| true), ", x");
+        assert_eq!(inner.byte_pos, 8);
+        assert!(cursor.subcursor(Span { start: 1, end: 1 }).is_eof());
+    }
+
+    #[test]
+    #[should_panic]
+    fn subcursor_not_char_boundary() {
+        let cursor = Cursor::new("竜");
+        cursor.subcursor(Span { start: 0, end: 1 });
+    }
+
+    #[test]
+    #[should_panic]
+    fn subcursor_reversed() {
+        let cursor = Cursor::new("abc");
+        cursor.subcursor(Span { start: 2, end: 1 });
+    }
+
+    #[test]
+    fn bump_delimited() {
+        let mut cursor = Cursor::new("\"竜 (\"x\"y");
+        assert_eq!(cursor.bump_delimited('"', '"'), Ok("竜 ("));
+        assert_eq!(cursor.byte_pos, 7);
+        assert_eq!(cursor.chars.as_str(), "x\"y");
+
+        let error = cursor.bump_delimited('"', '"').unwrap_err();
+        assert_eq!(
+            error,
+            UnterminatedDelimiter {
+                open: '"',
+                close: '"',
+                span: Span { start: 7, end: 7 }
+            }
+        );
+        assert_eq!(format!("{error}"), "expected '\"' at byte position 7");
+
+        cursor.bump();
+        let error = cursor.bump_delimited('"', '"').unwrap_err();
+        assert_eq!(error.span, Span { start: 8, end: 9 });
+        assert_eq!(
+            format!("{error}"),
+            "unterminated '\"', expected '\"' at byte position 8"
+        );
+        assert_eq!(cursor.byte_pos, 8);
+        assert_eq!(cursor.chars.as_str(), "\"y");
+
+        let mut cursor = Cursor::new("[]");
+        assert_eq!(cursor.bump_delimited('[', ']'), Ok(""));
+        assert!(cursor.is_eof());
+    }
+
+    #[test]
+    fn bump_balanced() {
+        let mut cursor = Cursor::new("(a (b (竜)) c)d");
+        assert_eq!(cursor.bump_balanced('(', ')'), Ok("a (b (竜)) c"));
+        assert_eq!(cursor.chars.as_str(), "d");
+        assert_eq!(
+            cursor.bump_balanced('(', ')'),
+            Err(UnbalancedDelimiter {
+                open: '(',
+                close: ')',
+                span: Span { start: 15, end: 15 }
+            })
+        );
+
+        let mut cursor = Cursor::with_offset("x(a (b) c", 1);
+        cursor.bump();
+        let error = cursor.bump_balanced('(', ')').unwrap_err();
+        assert_eq!(error.span, Span { start: 2, end: 3 });
+        assert_eq!(
+            format!("{error}"),
+            "unmatched '(', expected ')' at byte position 2"
+        );
+        assert_eq!(cursor.byte_pos, 2);
+        assert_eq!(cursor.chars.as_str(), "(a (b) c");
+
+        let deep = "[".repeat(1000) + &"]".repeat(1000);
+        let mut cursor = Cursor::new(&deep);
+        assert_eq!(cursor.bump_balanced('[', ']'), Ok(&deep[1..1999]));
+        assert!(cursor.is_eof());
+
+        // Identical delimiters don't nest
+        let mut cursor = Cursor::new("|a|b|");
+        assert_eq!(cursor.bump_balanced('|', '|'), Ok("a"));
+        assert_eq!(cursor.chars.as_str(), "b|");
+        let mut cursor = Cursor::new("|a");
+        assert_eq!(
+            cursor.bump_balanced('|', '|'),
+            Err(UnbalancedDelimiter {
+                open: '|',
+                close: '|',
+                span: Span { start: 0, end: 1 }
+            })
+        );
+        assert_eq!(cursor.byte_pos, 0);
+    }
+
+    #[test]
+    fn lex_each() {
+        let mut buffer = [Span { start: 0, end: 0 }; 4];
+        let mut len = 0;
+        let mut cursor = Cursor::new("ab 竜 c!");
+        cursor.lex_each(
+            |cursor| {
+                cursor.skip_whitespace();
+                let start = cursor.byte_pos;
+                cursor.skip_while_nonempty(char::is_alphabetic);
+                Some(cursor.span_from(start))
+            },
+            |span, token| {
+                assert!(!token.contains(' '));
+                buffer[len] = span;
+                len += 1;
+            },
+        );
+        // Lexing stops at the first token which doesn't make progress, without passing it on
+        assert_eq!(
+            buffer[..len],
+            [
+                Span { start: 0, end: 2 },
+                Span { start: 3, end: 6 },
+                Span { start: 7, end: 8 },
+            ]
+        );
+        assert_eq!(cursor.chars.as_str(), "!");
+
+        let mut count = 0;
+        cursor.lex_each(|_| None, |_, _| count += 1);
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn char_at() {
+        let mut cursor = Cursor::with_offset("a竜b", 1);
+        cursor.bump_n(2);
+        assert_eq!(cursor.char_at(1), Some('a'));
+        assert_eq!(cursor.char_at(2), Some('竜'));
+        assert_eq!(cursor.char_at(3), None);
+        assert_eq!(cursor.char_at(4), None);
+        assert_eq!(cursor.char_at(5), Some('b'));
+        assert_eq!(cursor.char_at(6), None);
+        assert_eq!(cursor.char_at(0), None);
+        assert_eq!(cursor.byte_pos, 5);
+    }
+
+    #[test]
+    fn is_eof_ignoring_whitespace() {
+        let mut cursor = Cursor::new("x \t\r\n \u{3000}");
+        assert!(!cursor.is_eof_ignoring_whitespace());
+        cursor.bump();
+        assert!(cursor.is_eof_ignoring_whitespace());
+        assert!(!cursor.is_eof());
+        assert_eq!(cursor.byte_pos, 1);
+        assert!(Cursor::new("").is_eof_ignoring_whitespace());
+    }
+
+    #[test]
+    fn bump_while_ranges() {
+        let ranges = [
+            '0'..='9',
+            'A'..='Z',
+            '_'..='_',
+            'a'..='z',
+            '\u{4E00}'..='\u{9FFF}',
+        ];
+        let mut cursor = Cursor::new("a_Z9竜 x");
+        assert_eq!(cursor.bump_while_ranges(&ranges), "a_Z9竜");
+        assert_eq!(cursor.byte_pos, 7);
+        assert_eq!(cursor.bump_while_ranges(&ranges), "");
+        assert_eq!(cursor.chars.as_str(), " x");
+        assert_eq!(cursor.bump_while_ranges(&[]), "");
+    }
+
+    #[test]
+    fn try_parse() {
+        let mut cursor = Cursor::new("ab\n竜c").tracked();
+        cursor.bump();
+        let before = cursor.clone();
+
+        let pair =
+            |cursor: &mut TrackedCursor<'_>| Some((cursor.bump()?, cursor.bump_if(|c| c == 'x')?));
+        assert_eq!(cursor.try_parse(pair), None);
+        assert_eq!(cursor, before);
+        assert_eq!(cursor.char_pos(), before.char_pos());
+        assert_eq!(cursor.position(), before.position());
+
+        assert_eq!(
+            cursor.try_parse(|cursor| cursor.eat_str("b\n竜").then_some(1)),
+            Some(1)
+        );
+        assert_eq!(cursor.byte_pos, 6);
+        assert_eq!(cursor.line(), 2);
+        assert_eq!(cursor.column(), 1);
+        assert_eq!(cursor.chars.as_str(), "c");
+    }
+
+    #[test]
+    fn tracked() {
+        let mut cursor = Cursor::with_offset("a\r\n\tb竜", 3);
+        cursor.bump_n(4);
+
+        // The consumed input is rescanned
+        let mut cursor = cursor.tracked();
+        assert_eq!(cursor.byte_pos(), 7);
+        assert_eq!(cursor.char_pos(), 4);
+        assert_eq!(
+            cursor.position(),
+            Position {
+                byte: 7,
+                line: 2,
+                column: 1
+            }
+        );
+        cursor.bump_n(2);
+        assert_eq!(cursor.char_pos(), 6);
+        assert_eq!(cursor.column(), 3);
+    }
+
+    #[test]
+    fn untracked_size() {
+        // Untracked cursors don't store any line and column tracking state
+        assert_eq!(
+            size_of::<Cursor<'_>>(),
+            size_of::<&str>() + size_of::<Chars<'_>>() + 2 * size_of::<usize>()
+        );
+        assert_eq!(
+            size_of::<Checkpoint<'_>>(),
+            size_of::<Chars<'_>>() + 2 * size_of::<usize>()
+        );
+    }
+
+    #[test]
+    fn untracked_seek_to_and_bump_back() {
+        let mut cursor = Cursor::new("a\n\t竜");
+        cursor.seek_to(6);
+        assert!(cursor.is_eof());
+        cursor.seek_to(2);
+        assert_eq!(cursor.chars.as_str(), "\t竜");
+        assert_eq!(cursor.bump_back(), Some('\n'));
+        assert_eq!(cursor.byte_pos, 1);
+        assert_eq!(cursor.bump_back(), Some('a'));
+        assert_eq!(cursor.bump_back(), None);
+        assert!(cursor.eat_str("a\n\t"));
+        assert_eq!(cursor.chars.as_str(), "竜");
     }
 }